@@ -0,0 +1,206 @@
+//! Compile-time checks of `#[gonfig(default = "...")]` literals against the
+//! field type, so that a typo fails the build instead of `build()`.
+
+use serde_json::Value;
+
+/// The value the runtime makes of a raw default: JSON when it parses,
+/// otherwise a plain string.
+fn parse(raw: &str) -> Value {
+    raw.parse()
+        .unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Check `raw` against `ty`, describing the mismatch on failure.
+///
+/// Types the macro cannot see into, e.g. other structs and enums, are
+/// accepted; `one_of` lists the known values of those.
+pub(crate) fn check(raw: &str, ty: &syn::Type, one_of: Option<&[syn::Lit]>) -> Result<(), String> {
+    let value = parse(raw);
+    check_value(&value, Some(raw.trim()), ty).map_err(|expected| {
+        let mut message = format!("invalid default `{}`: expected {}", raw, expected);
+        if matches!(value, Value::Number(_) | Value::Bool(_)) && expected == "a string" {
+            message.push_str(&format!(
+                ", write `default = \"\\\"{}\\\"\"` for the string",
+                raw
+            ));
+        }
+        message
+    })?;
+
+    match one_of {
+        Some(allowed)
+            if !allowed
+                .iter()
+                .any(|lit| lit_value(lit).as_ref() == Some(&value)) =>
+        {
+            Err(format!(
+                "invalid default `{}`: not one of the `one_of` values",
+                raw
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The string of a default selecting a variant of `ty`, with `ty` looked
+/// through `Option`, when `ty` is none of the types [`check`] knows, e.g. an
+/// enum. Whether it names a variant is checked by the compiler, see
+/// `__private::EnumVariants`.
+pub(crate) fn variant_selector<'a>(
+    raw: &str,
+    ty: &'a syn::Type,
+) -> Option<(String, &'a syn::Type)> {
+    let Value::String(selector) = parse(raw) else {
+        return None;
+    };
+    let mut ty = ty;
+    loop {
+        let segment = crate::last_segment(ty)?;
+        match segment.ident.to_string().as_str() {
+            "Option" => ty = crate::generic_argument(segment)?,
+            known if KNOWN_TYPES.contains(&known) => return None,
+            _ => return Some((selector, ty)),
+        }
+    }
+}
+
+const KNOWN_TYPES: &[&str] = &[
+    "bool", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+    "f32", "f64", "String", "PathBuf", "char", "Vec", "VecDeque", "HashSet", "BTreeSet", "HashMap",
+    "BTreeMap", "Value", "Duration",
+];
+
+/// Check a parsed default against `ty`, returning what was expected. `raw`
+/// is the literal the value was parsed from, if any, so that integers beyond
+/// the range of JSON numbers are read in the field's own width.
+fn check_value(value: &Value, raw: Option<&str>, ty: &syn::Type) -> Result<(), String> {
+    let Some(segment) = crate::last_segment(ty) else {
+        return Ok(());
+    };
+    let inner = crate::generic_argument(segment);
+
+    match segment.ident.to_string().as_str() {
+        "Option" => match (value, inner) {
+            (Value::Null, _) | (_, None) => Ok(()),
+            (value, Some(inner)) => check_value(value, raw, inner),
+        },
+        "bool" => match value {
+            Value::Bool(_) => Ok(()),
+            _ => Err("`true` or `false`".to_string()),
+        },
+        int @ ("i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+        | "u128" | "usize") => {
+            let digits = match (value, raw) {
+                (Value::Number(_), Some(raw)) => raw.to_string(),
+                (Value::Number(n), None) => n.to_string(),
+                _ => String::new(),
+            };
+            check_int(&digits, Some(int), "defaults")
+        }
+        "f32" | "f64" => match value {
+            Value::Number(_) => Ok(()),
+            _ => Err("a number".to_string()),
+        },
+        "String" | "PathBuf" => match value {
+            Value::String(_) => Ok(()),
+            _ => Err("a string".to_string()),
+        },
+        "char" => match value {
+            Value::String(s) if s.chars().count() == 1 => Ok(()),
+            _ => Err("a single character".to_string()),
+        },
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => match (value, inner) {
+            (Value::Array(items), Some(inner)) => items
+                .iter()
+                .try_for_each(|item| check_value(item, None, inner))
+                .map_err(|expected| format!("a JSON array of {}", expected)),
+            (Value::Array(_), None) => Ok(()),
+            _ => Err("a JSON array, e.g. `[\"a\", \"b\"]`".to_string()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Check the integer literal `lit` of a `one_of` list against `ty`, looked
+/// through `Option`, describing the mismatch on failure.
+pub(crate) fn check_one_of_int(lit: &syn::LitInt, ty: &syn::Type) -> Result<(), String> {
+    let mut ty = ty;
+    let int = loop {
+        let Some(segment) = crate::last_segment(ty) else {
+            break None;
+        };
+        let ident = segment.ident.to_string();
+        match crate::generic_argument(segment) {
+            Some(inner) if ident == "Option" => ty = inner,
+            _ => break INT_TYPES.iter().copied().find(|int| *int == ident),
+        }
+    };
+    check_int(lit.base10_digits(), int, "`one_of` values").map_err(|expected| {
+        format!(
+            "invalid `one_of` value `{}`: expected {}",
+            lit.base10_digits(),
+            expected
+        )
+    })
+}
+
+const INT_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// Check the decimal `digits` of an integer against the integer type `int`,
+/// if known, returning what was expected. `what` names the values in the
+/// message about integers beyond 64 bits.
+fn check_int(digits: &str, int: Option<&str>, what: &str) -> Result<(), String> {
+    if let Some(int) = int {
+        let (min, max) = int_bounds(int);
+        let fits = match (digits.parse::<u128>(), digits.parse::<i128>()) {
+            (Ok(n), _) => n <= max,
+            (Err(_), Ok(n)) => n >= min,
+            _ => false,
+        };
+        if !fits {
+            return Err(format!("an integer that fits in `{}`", int));
+        }
+    }
+    // Values travel as JSON numbers, which hold 64 bits
+    let representable = digits.parse::<u64>().is_ok() || digits.parse::<i64>().is_ok();
+    match (representable, int) {
+        (true, _) => Ok(()),
+        (false, Some(int)) => Err(format!(
+            "an integer from {} to {}; larger `{}` values cannot be set as {}",
+            i64::MIN,
+            u64::MAX,
+            int,
+            what
+        )),
+        (false, None) => Err(format!("an integer from {} to {}", i64::MIN, u64::MAX)),
+    }
+}
+
+/// The smallest and largest values of the integer type `ty`.
+fn int_bounds(ty: &str) -> (i128, u128) {
+    match ty {
+        "i8" => (i8::MIN.into(), i8::MAX as u128),
+        "i16" => (i16::MIN.into(), i16::MAX as u128),
+        "i32" => (i32::MIN.into(), i32::MAX as u128),
+        "i64" | "isize" => (i64::MIN.into(), i64::MAX as u128),
+        "i128" => (i128::MIN, i128::MAX as u128),
+        "u8" => (0, u8::MAX.into()),
+        "u16" => (0, u16::MAX.into()),
+        "u32" => (0, u32::MAX.into()),
+        "u64" | "usize" => (0, u64::MAX.into()),
+        _ => (0, u128::MAX),
+    }
+}
+
+/// The JSON value of a `one_of` literal.
+fn lit_value(lit: &syn::Lit) -> Option<Value> {
+    match lit {
+        syn::Lit::Str(s) => Some(Value::String(s.value())),
+        syn::Lit::Bool(b) => Some(Value::Bool(b.value)),
+        syn::Lit::Int(i) => i.base10_digits().parse().ok(),
+        syn::Lit::Float(f) => f.base10_digits().parse().ok(),
+        _ => None,
+    }
+}
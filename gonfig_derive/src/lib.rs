@@ -1,45 +1,90 @@
-use darling::{FromDeriveInput, FromField};
+use darling::{FromDeriveInput, FromField, FromMeta, FromVariant};
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use quote::{quote, quote_spanned};
+use std::collections::HashMap;
+use syn::{ext::IdentExt, parse_macro_input, spanned::Spanned, DeriveInput};
+
+mod defaults;
+mod serde_attrs;
+
+use serde_attrs::{RenameRule, SerdeContainer, SerdeItem};
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(gonfig, Gonfig))]
+#[darling(attributes(gonfig, Gonfig), forward_attrs(serde, doc))]
 struct GonfigOpts {
     ident: syn::Ident,
     generics: syn::Generics,
-    data: darling::ast::Data<(), GonfigField>,
+    data: darling::ast::Data<GonfigVariant, GonfigField>,
+    attrs: Vec<syn::Attribute>,
 
     #[darling(default)]
     env_prefix: Option<String>,
 
+    #[darling(default)]
+    allow_env: Option<bool>,
+
     #[darling(default)]
     allow_cli: bool,
 
     #[darling(default)]
     allow_config: bool,
+
+    #[darling(default)]
+    allow_set: bool,
+
+    #[darling(default)]
+    config_file: Option<String>,
+
+    #[darling(default)]
+    config_file_env: Option<String>,
+
+    #[darling(default)]
+    config_file_cli: Option<String>,
+
+    #[darling(default)]
+    config_optional: bool,
+
+    #[darling(default)]
+    default: bool,
+
+    #[darling(default)]
+    rename_all_env: Option<RenameRule>,
+
+    #[darling(default)]
+    rename_all_cli: Option<RenameRule>,
+}
+
+#[derive(Debug, FromVariant)]
+#[darling(attributes(gonfig), forward_attrs(serde, doc))]
+struct GonfigVariant {
+    ident: syn::Ident,
+    fields: darling::ast::Fields<GonfigField>,
+    attrs: Vec<syn::Attribute>,
 }
 
 #[derive(Debug, FromField)]
-#[darling(attributes(gonfig, skip_gonfig, skip))]
+#[darling(attributes(gonfig), forward_attrs(serde, doc, skip, skip_gonfig))]
 struct GonfigField {
     ident: Option<syn::Ident>,
 
-    // Reserved for future use (flatten feature)
-    #[allow(dead_code)]
     ty: syn::Type,
 
+    attrs: Vec<syn::Attribute>,
+
+    #[darling(default)]
+    env_name: Option<darling::util::SpannedValue<String>>,
+
     #[darling(default)]
-    env_name: Option<String>,
+    short: Option<darling::util::SpannedValue<char>>,
 
     #[darling(default)]
-    cli_name: Option<String>,
+    cli_name: Option<darling::util::SpannedValue<String>>,
 
     #[darling(default)]
-    skip_gonfig: bool,
+    skip_gonfig: darling::util::Flag,
 
     #[darling(default)]
-    skip: bool,
+    skip: darling::util::Flag,
 
     // Reserved for future use (flatten feature)
     #[allow(dead_code)]
@@ -47,7 +92,222 @@ struct GonfigField {
     flatten: bool,
 
     #[darling(default)]
-    default: Option<String>,
+    default: Option<darling::util::Override<darling::util::SpannedValue<String>>>,
+
+    #[darling(default)]
+    default_fn: Option<syn::Path>,
+
+    #[darling(default)]
+    range: Option<RangeOpts>,
+
+    #[darling(default)]
+    length: Option<LengthOpts>,
+
+    #[darling(default)]
+    regex: Option<darling::util::SpannedValue<String>>,
+
+    #[darling(default)]
+    one_of: Option<LitList>,
+
+    #[darling(default)]
+    url: bool,
+
+    #[darling(default)]
+    non_empty: bool,
+
+    #[darling(default)]
+    custom: Option<syn::Path>,
+
+    #[darling(default)]
+    required_if: Option<String>,
+
+    #[darling(default)]
+    conflicts_with: Option<String>,
+
+    #[darling(default)]
+    requires: Option<String>,
+
+    #[darling(default)]
+    sources: Option<SourceList>,
+
+    #[darling(default)]
+    no_env: bool,
+
+    #[darling(default)]
+    alias: Option<String>,
+
+    #[darling(default)]
+    deprecated_env: Option<String>,
+
+    #[darling(default)]
+    deprecated_key: Option<String>,
+
+    #[darling(default)]
+    sensitive: bool,
+
+    #[darling(default)]
+    subcommand: bool,
+
+    #[darling(default)]
+    positional: Option<darling::util::Override<PositionalOpts>>,
+
+    #[darling(default)]
+    parse_with: Option<syn::Path>,
+}
+
+impl GonfigField {
+    /// The span of `#[skip]`, `#[skip_gonfig]` or `#[gonfig(skip)]`, when
+    /// the field is skipped.
+    fn skipped(&self) -> Option<proc_macro2::Span> {
+        [&self.skip, &self.skip_gonfig]
+            .into_iter()
+            .find(|flag| flag.is_present())
+            .map(|flag| flag.span())
+            .or_else(|| {
+                self.attrs
+                    .iter()
+                    .find(|a| a.path().is_ident("skip") || a.path().is_ident("skip_gonfig"))
+                    .map(|a| a.path().span())
+            })
+    }
+
+    /// The span to report field-level errors at.
+    fn span(&self) -> proc_macro2::Span {
+        match &self.ident {
+            Some(ident) => ident.span(),
+            None => self.ty.span(),
+        }
+    }
+
+    /// The names of the `gonfig` options set on the field, other than `skip`.
+    fn options(&self) -> Vec<&'static str> {
+        [
+            ("env_name", self.env_name.is_some()),
+            ("short", self.short.is_some()),
+            ("cli_name", self.cli_name.is_some()),
+            ("default", self.default.is_some()),
+            ("default_fn", self.default_fn.is_some()),
+            ("range", self.range.is_some()),
+            ("length", self.length.is_some()),
+            ("regex", self.regex.is_some()),
+            ("one_of", self.one_of.is_some()),
+            ("url", self.url),
+            ("non_empty", self.non_empty),
+            ("custom", self.custom.is_some()),
+            ("required_if", self.required_if.is_some()),
+            ("conflicts_with", self.conflicts_with.is_some()),
+            ("requires", self.requires.is_some()),
+            ("sources", self.sources.is_some()),
+            ("no_env", self.no_env),
+            ("alias", self.alias.is_some()),
+            ("deprecated_env", self.deprecated_env.is_some()),
+            ("deprecated_key", self.deprecated_key.is_some()),
+            ("sensitive", self.sensitive),
+            ("subcommand", self.subcommand),
+            ("positional", self.positional.is_some()),
+            ("parse_with", self.parse_with.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+}
+
+/// `range(min = 1, max = 65535)`
+#[derive(Debug, Default, FromMeta)]
+struct RangeOpts {
+    #[darling(default)]
+    min: Option<Number>,
+
+    #[darling(default)]
+    max: Option<Number>,
+}
+
+/// `positional` or `positional(index = 0)`
+#[derive(Debug, Default, FromMeta)]
+struct PositionalOpts {
+    #[darling(default)]
+    index: Option<usize>,
+}
+
+/// `length(min = 1, max = 64)`
+#[derive(Debug, Default, FromMeta)]
+struct LengthOpts {
+    #[darling(default)]
+    min: Option<usize>,
+
+    #[darling(default)]
+    max: Option<usize>,
+}
+
+/// `one_of = ["a", "b"]` or `one_of = [1, 2]`
+#[derive(Debug, Default)]
+struct LitList(Vec<syn::Lit>);
+
+impl FromMeta for LitList {
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        match expr {
+            syn::Expr::Array(array) => array
+                .elems
+                .iter()
+                .map(|elem| match elem {
+                    syn::Expr::Lit(lit) => Ok(lit.lit.clone()),
+                    _ => Err(darling::Error::custom("expected a literal").with_span(elem)),
+                })
+                .collect::<darling::Result<_>>()
+                .map(LitList),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+    }
+}
+
+/// `sources = "file,cli"` - the sources a field may be read from.
+#[derive(Debug, Clone)]
+struct SourceList(Vec<&'static str>);
+
+impl FromMeta for SourceList {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|source| match source {
+                "env" | "environment" => Ok("Environment"),
+                "file" | "config" => Ok("ConfigFile"),
+                "cli" => Ok("Cli"),
+                other => Err(darling::Error::unknown_value(other)),
+            })
+            .collect::<darling::Result<_>>()
+            .map(SourceList)
+    }
+}
+
+/// A numeric literal, integer or float, optionally negated.
+#[derive(Debug, Clone, Copy)]
+struct Number(f64);
+
+impl FromMeta for Number {
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        match value {
+            syn::Lit::Int(i) => Ok(Number(i.base10_parse()?)),
+            syn::Lit::Float(f) => Ok(Number(f.base10_parse()?)),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        match expr {
+            syn::Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Neg(_),
+                expr,
+                ..
+            }) => Self::from_expr(expr).map(|Number(n)| Number(-n)),
+            syn::Expr::Lit(lit) => Self::from_value(&lit.lit),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+    }
 }
 
 #[proc_macro_derive(Gonfig, attributes(gonfig, skip_gonfig, skip, Gonfig))]
@@ -57,6 +317,13 @@ pub fn derive_gonfig(input: TokenStream) -> TokenStream {
         Ok(opts) => opts,
         Err(e) => return TokenStream::from(e.write_errors()),
     };
+    let mut errors = darling::Error::accumulator();
+    errors.handle(check_attributes(&opts));
+    errors.handle(check_defaults(&opts));
+    errors.handle(check_validators(&opts));
+    if let Err(e) = errors.finish() {
+        return TokenStream::from(e.write_errors());
+    }
 
     let expanded = generate_gonfig_impl(&opts);
     TokenStream::from(expanded)
@@ -66,74 +333,293 @@ fn generate_gonfig_impl(opts: &GonfigOpts) -> proc_macro2::TokenStream {
     let name = &opts.ident;
     let (impl_generics, ty_generics, where_clause) = opts.generics.split_for_impl();
 
-    let allow_env = true; // Always enable environment variables by default
+    let allow_env = opts.allow_env.unwrap_or(true);
     let allow_cli = opts.allow_cli;
-    let allow_config = opts.allow_config;
+    let allow_set = opts.allow_set;
+    let config_files = config_files_tokens(opts);
+    // Explicit config file locations replace the default lookup, as does
+    // passing the config file flag
+    let allow_config =
+        opts.allow_config && opts.config_file.is_none() && opts.config_file_env.is_none();
+    let config_flag_given = match config_cli_flag(opts) {
+        Some(flag) => quote! { cli.get_matches().contains_key(#flag) },
+        None => quote! { false },
+    };
 
-    let env_prefix = opts.env_prefix.as_ref().cloned().unwrap_or_default();
+    let load_config_files = config_files.as_ref().map(|files| {
+        quote! {
+            builder = builder.with_config_files_deferred(&#files, &cli);
+        }
+    });
+    // Built-in flags give way to fields with the same name
+    let add_config_arg = config_files.as_ref().map(|files| {
+        quote! {
+            let command = match #files.cli_arg() {
+                ::std::option::Option::Some(arg)
+                    if !command.get_arguments().any(|a| a.get_id() == arg.get_id()) =>
+                {
+                    command.arg(arg)
+                }
+                _ => command,
+            };
+        }
+    });
+    let add_print_config_arg = allow_cli.then(|| {
+        quote! {
+            let arg = ::gonfig::Cli::print_config_arg();
+            let command = if command.get_arguments().any(|a| a.get_id() == arg.get_id()) {
+                command
+            } else {
+                command.arg(arg)
+            };
+        }
+    });
+    let add_set_args = allow_set.then(|| {
+        quote! {
+            let command = command.args(::gonfig::Cli::set_args());
+        }
+    });
+    let add_man_files = match &config_files {
+        Some(files) => quote! { let page = page.config_files(&#files); },
+        None if allow_config => quote! {
+            let page = ["config.toml", "config.yaml", "config.json"]
+                .into_iter()
+                .fold(page, |page, path| {
+                    page.file(path, "Configuration file in the working directory; the first one found is loaded.")
+                });
+        },
+        None => quote! {},
+    };
+    let generate_fns = allow_cli.then(|| {
+        quote! {
+            /// Write the shell completion script for `shell` to `out`.
+            pub fn gonfig_completions(shell: ::gonfig::generate::Shell, out: &mut dyn ::std::io::Write) {
+                ::gonfig::generate::completions(Self::gonfig_command(), shell, out)
+            }
 
-    let fields = opts
-        .data
-        .as_ref()
-        .take_struct()
-        .expect("Only structs are supported")
-        .fields;
+            /// Write the man page to `out`, listing the environment variables
+            /// and configuration files read.
+            pub fn gonfig_man_page(out: &mut dyn ::std::io::Write) -> ::gonfig::Result<()> {
+                let schema = <Self as ::gonfig::schema::GonfigSchema>::gonfig_schema();
+                let page = ::gonfig::generate::ManPage::new(Self::gonfig_command());
+                let page = if #allow_env { page.env_vars_from(&schema) } else { page };
+                #add_man_files
+                page.render(out)
+            }
+        }
+    });
+    let about = doc_comment(&opts.attrs).map(|doc| quote! { .about(#doc) });
+    let struct_default = opts.default.then(|| {
+        quote! {
+            .default_fn(|| ::gonfig::schema::__private::default_value(
+                <Self as ::std::default::Default>::default()
+            ))
+        }
+    });
 
-    // Separate regular fields from flattened fields
-    let mut regular_mappings = Vec::new();
-    let mut default_mappings = Vec::new();
+    let env_prefix = opts.env_prefix.as_ref().cloned().unwrap_or_default();
 
-    for f in fields.iter().filter(|f| !f.skip_gonfig && !f.skip) {
-        let field_name = f.ident.as_ref().unwrap();
-        let field_str = field_name.to_string();
+    let name_str = name.to_string();
+    let serde_container = SerdeContainer::from_attrs(&opts.attrs);
 
-        // Note: flatten feature is not yet fully implemented
-        // For now, treat all fields as regular fields
-        {
-            // Generate expected environment variable name
-            let env_key = if let Some(custom_name) = &f.env_name {
-                // Use custom name directly if provided
-                custom_name.clone()
-            } else if !env_prefix.is_empty() {
-                // Use prefix + field name pattern
-                format!("{}_{}", env_prefix, field_str.to_uppercase())
-            } else {
-                // Just field name in uppercase
-                field_str.to_uppercase()
+    let schema_body = match &opts.data {
+        darling::ast::Data::Struct(fields) => {
+            let naming = Naming::new(opts, serde_container.rename_all);
+            let schema_fields = schema_fields(fields.iter(), &naming);
+            quote! { #(.field(#schema_fields))* }
+        }
+        darling::ast::Data::Enum(variants) => {
+            let tagging = match (&serde_container.tag, &serde_container.content) {
+                _ if serde_container.untagged => {
+                    quote! { ::gonfig::schema::EnumTagging::Untagged }
+                }
+                (Some(tag), Some(content)) => quote! {
+                    ::gonfig::schema::EnumTagging::Adjacent { tag: #tag, content: #content }
+                },
+                (Some(tag), None) => {
+                    quote! { ::gonfig::schema::EnumTagging::Internal { tag: #tag } }
+                }
+                _ => quote! { ::gonfig::schema::EnumTagging::External },
             };
 
-            // Generate CLI argument name (kebab-case)
-            let cli_key = if let Some(custom_name) = &f.cli_name {
-                custom_name.clone()
-            } else {
-                field_str.replace('_', "-")
-            };
+            let schema_variants = variants.iter().map(|v| {
+                let serde_variant = SerdeItem::from_attrs(&v.attrs);
+                let variant_name = variant_name(v, &serde_container);
+
+                let variant_fields = match v.fields.style {
+                    darling::ast::Style::Struct => {
+                        let rename_all = serde_variant
+                            .rename_all
+                            .or(serde_container.rename_all_fields);
+                        let naming = Naming::new(opts, rename_all);
+                        let schema_fields = schema_fields(v.fields.iter(), &naming);
+                        quote! { #(.field(#schema_fields))* }
+                    }
+                    // A newtype variant carries the fields of its inner type
+                    darling::ast::Style::Tuple if v.fields.len() == 1 => {
+                        let probe = nested_schema_probe(&v.fields.fields[0].ty);
+                        quote! { .fields_from(#probe) }
+                    }
+                    _ => quote! {},
+                };
+
+                let variant_about = doc_comment(&v.attrs).map(|doc| quote! { .about(#doc) });
 
-            regular_mappings.push(quote! {
-                (#field_str.to_string(), #env_key.to_string(), #cli_key.to_string())
+                quote! {
+                    .variant(
+                        ::gonfig::schema::VariantSchema::new(#variant_name)
+                            #variant_about
+                            #variant_fields
+                    )
+                }
             });
 
-            // Handle default values
-            if let Some(default_value) = &f.default {
-                default_mappings.push(quote! {
-                    (#field_str.to_string(), #default_value.to_string())
-                });
-            }
+            quote! { .tagging(#tagging) #(#schema_variants)* }
         }
-    }
+    };
+
+    // Unit variants a string can select, so that string defaults of fields
+    // holding the enum are checked when they are compiled
+    let enum_variants = match &opts.data {
+        darling::ast::Data::Enum(variants)
+            if serde_container.tag.is_none() && !serde_container.untagged =>
+        {
+            let names = variants
+                .iter()
+                .filter(|v| v.fields.style == darling::ast::Style::Unit)
+                .map(|v| variant_name(v, &serde_container));
+            Some(quote! {
+                impl #impl_generics ::gonfig::schema::__private::EnumVariants for #name #ty_generics #where_clause {
+                    const VARIANTS: &'static [&'static str] = &[#(#names),*];
+                }
+            })
+        }
+        _ => None,
+    };
+    let variant_checks = variant_default_checks(opts);
 
     quote! {
+        #enum_variants
+        #(#variant_checks)*
+
+        impl #impl_generics ::gonfig::schema::GonfigSchema for #name #ty_generics #where_clause {
+            fn gonfig_schema() -> ::gonfig::schema::StructSchema {
+                ::gonfig::schema::StructSchema::new(#name_str)
+                    .env_prefix(#env_prefix)
+                    #about
+                    #struct_default
+                    #schema_body
+            }
+        }
+
         impl #impl_generics #name #ty_generics #where_clause {
             pub fn from_gonfig() -> ::gonfig::Result<Self> {
                 Self::from_gonfig_with_builder(::gonfig::ConfigBuilder::new())
             }
 
-            pub fn from_gonfig_with_builder(mut builder: ::gonfig::ConfigBuilder) -> ::gonfig::Result<Self> {
-                // Regular field mappings: (field_name, env_key, cli_key)
-                let field_mappings: Vec<(String, String, String)> = vec![#(#regular_mappings),*];
+            /// Load the configuration into `builder`'s sources, reading the
+            /// process arguments leniently: arguments that are not fields, such
+            /// as those of a test harness, are ignored. Use
+            /// `from_gonfig_with_args` to check them with `gonfig_command()`.
+            ///
+            /// With `allow_cli`, `--print-config` returns the effective
+            /// configuration, rendered, as `Error::PrintConfig`.
+            pub fn from_gonfig_with_builder(builder: ::gonfig::ConfigBuilder) -> ::gonfig::Result<Self> {
+                let (builder, cli) = Self::gonfig_sources(builder, ::std::env::args_os(), false);
+                Self::gonfig_build(builder, &cli)
+            }
 
-                // Default value mappings: (field_name, default_value)
-                let default_values: Vec<(String, String)> = vec![#(#default_mappings),*];
+            /// Load the configuration, parsing `args` (including the program
+            /// name) with `gonfig_command()` instead of reading the process
+            /// arguments leniently. Unknown flags and invalid values are
+            /// errors, and `--help` and `--version` print and exit as in clap.
+            pub fn from_gonfig_with_args<I, T>(builder: ::gonfig::ConfigBuilder, args: I) -> ::gonfig::Result<Self>
+            where
+                I: ::std::iter::IntoIterator<Item = T>,
+                T: ::std::convert::Into<::std::ffi::OsString> + ::std::clone::Clone,
+            {
+                let (builder, cli) = Self::gonfig_sources(builder, args, true);
+                Self::gonfig_build(builder, &cli)
+            }
+
+            fn gonfig_build(builder: ::gonfig::ConfigBuilder, cli: &::gonfig::Cli) -> ::gonfig::Result<Self> {
+                // The rendered configuration is returned for the caller to print
+                if #allow_cli {
+                    if let ::std::option::Option::Some(format) = cli.print_config_format()? {
+                        let rendered = builder.render_config(format)?;
+                        return ::std::result::Result::Err(::gonfig::Error::PrintConfig(rendered));
+                    }
+                }
+
+                // Build the final configuration with explicit type
+                builder.build::<Self>()
+            }
+
+            /// The clap command parsing this configuration's CLI flags.
+            pub fn gonfig_command() -> ::gonfig::clap::Command {
+                let schema = <Self as ::gonfig::schema::GonfigSchema>::gonfig_schema();
+                let command = ::gonfig::Cli::command(&schema)
+                    .name(env!("CARGO_PKG_NAME"))
+                    .version(env!("CARGO_PKG_VERSION"));
+                #add_config_arg
+                #add_print_config_arg
+                #add_set_args
+                command
+            }
+
+            #generate_fns
+
+            /// An annotated starter config file with every key, its default or
+            /// a placeholder, and doc comments as comments.
+            pub fn template(format: ::gonfig::ConfigFormat) -> ::std::string::String {
+                let schema = <Self as ::gonfig::schema::GonfigSchema>::gonfig_schema();
+                ::gonfig::generate::template(&schema, format)
+            }
+
+            /// Reference documentation of the environment variables read by
+            /// this configuration, with their types, defaults, descriptions,
+            /// CLI flags and file keys.
+            pub fn env_reference() -> ::gonfig::generate::EnvReference {
+                if !#allow_env {
+                    return ::gonfig::generate::EnvReference::default();
+                }
+                let schema = <Self as ::gonfig::schema::GonfigSchema>::gonfig_schema();
+                let reference = ::gonfig::generate::EnvReference::from_schema(&schema);
+                if #allow_cli {
+                    reference
+                } else {
+                    reference.without_cli_flags()
+                }
+            }
+
+            /// A builder with every source read by `from_gonfig`, reading the
+            /// process arguments leniently.
+            ///
+            /// Config files are read and defaults computed when the
+            /// configuration is built, so errors are returned by
+            /// `build` and more sources can be added first.
+            pub fn gonfig_builder() -> ::gonfig::ConfigBuilder {
+                Self::gonfig_sources(::gonfig::ConfigBuilder::new(), ::std::env::args_os(), false).0
+            }
+
+            /// Add the sources of this configuration to `builder`, returning
+            /// the parsed arguments alongside. `strict` parses the arguments
+            /// with `gonfig_command()` rather than leniently.
+            fn gonfig_sources<I, T>(
+                mut builder: ::gonfig::ConfigBuilder,
+                args: I,
+                strict: bool,
+            ) -> (::gonfig::ConfigBuilder, ::gonfig::Cli)
+            where
+                I: ::std::iter::IntoIterator<Item = T>,
+                T: ::std::convert::Into<::std::ffi::OsString> + ::std::clone::Clone,
+            {
+                let schema = <Self as ::gonfig::schema::GonfigSchema>::gonfig_schema();
+                let args: ::std::vec::Vec<::std::ffi::OsString> =
+                    args.into_iter().map(::std::convert::Into::into).collect();
+
+                // Leaf field mappings with names composed through nested structs
+                let field_mappings = schema.field_mappings();
 
                 if #allow_env {
                     // Create custom environment source with field mappings
@@ -143,104 +629,766 @@ fn generate_gonfig_impl(opts: &GonfigOpts) -> proc_macro2::TokenStream {
                         env = env.with_prefix(#env_prefix);
                     }
 
-                    // Apply field-level mappings for regular fields
-                    for (field_name, env_key, _cli_key) in &field_mappings {
-                        env = env.with_field_mapping(field_name, env_key);
+                    for mapping in &field_mappings {
+                        env = env.with_field_mapping(&mapping.path, &mapping.env_key);
+                        for alias in &mapping.env_aliases {
+                            env = env.with_field_alias(&mapping.path, alias);
+                        }
+                        if mapping.raw {
+                            env = env.with_raw_field(&mapping.path);
+                        }
+                    }
+
+                    // A nested section may also be set as a whole JSON object
+                    for (path, env_key) in schema.section_env_keys() {
+                        env = env.with_field_mapping(path, env_key);
                     }
 
                     builder = builder.with_env_custom(env);
                 }
 
-                if #allow_cli {
-                    // Create custom CLI source with field mappings
-                    let mut cli = ::gonfig::Cli::from_args();
-
-                    // Apply field-level CLI mappings for regular fields
-                    for (field_name, _env_key, cli_key) in &field_mappings {
-                        cli = cli.with_field_mapping(field_name, cli_key);
+                // Read the command line leniently, or parse it with the
+                // generated clap command; an invalid command line is then
+                // reported by `build`
+                let cli = if #allow_cli && !strict {
+                    let args = args.iter().map(|a| a.to_string_lossy().into_owned()).collect();
+                    let cli = ::gonfig::Cli::from_vec(args).with_schema(&schema).allow_set(#allow_set);
+                    builder = builder.with_cli_custom(cli.clone());
+                    cli
+                } else if #allow_cli {
+                    match ::gonfig::Cli::from_command_args(Self::gonfig_command(), &schema, args.clone()) {
+                        ::std::result::Result::Ok(cli) => {
+                            let cli = cli.allow_set(#allow_set);
+                            builder = builder.with_cli_custom(cli.clone());
+                            cli
+                        }
+                        ::std::result::Result::Err(e) => {
+                            let message = match e {
+                                ::gonfig::Error::Cli(message) => message,
+                                e => e.to_string(),
+                            };
+                            builder = builder.add_source_deferred(::gonfig::Source::Cli, move || {
+                                ::std::result::Result::Err(::gonfig::Error::Cli(message.clone()))
+                            });
+                            ::gonfig::Cli::from_vec(::std::vec::Vec::new())
+                        }
                     }
+                } else {
+                    ::gonfig::Cli::from_vec(args.iter().map(|a| a.to_string_lossy().into_owned()).collect())
+                };
 
-                    builder = builder.with_cli_custom(cli);
-                }
+                #load_config_files
 
-                if #allow_config {
-                    // Config file support - check for default config files
-                    use std::path::Path;
-
-                    if Path::new("config.toml").exists() {
-                        builder = match builder.with_file("config.toml") {
-                            Ok(b) => b,
-                            Err(e) => return Err(e),
-                        };
-                    } else if Path::new("config.yaml").exists() {
-                        builder = match builder.with_file("config.yaml") {
-                            Ok(b) => b,
-                            Err(e) => return Err(e),
-                        };
-                    } else if Path::new("config.json").exists() {
-                        builder = match builder.with_file("config.json") {
-                            Ok(b) => b,
-                            Err(e) => return Err(e),
-                        };
+                if #allow_config && !#config_flag_given {
+                    // Config file support - the first default config file found
+                    let found = ["config.toml", "config.yaml", "config.json"]
+                        .into_iter()
+                        .find(|path| ::std::path::Path::new(path).exists());
+                    if let ::std::option::Option::Some(path) = found {
+                        builder = builder.with_file_deferred(path);
                     }
                 }
 
-                // Apply default values
-                if !default_values.is_empty() {
-                    let mut defaults_json = ::serde_json::Map::new();
-                    for (field_name, default_value) in default_values {
-                        // Try to parse as JSON first, otherwise use as string
-                        let value = default_value.parse::<::serde_json::Value>()
-                            .unwrap_or_else(|_| ::serde_json::Value::String(default_value));
-                        defaults_json.insert(field_name, value);
-                    }
-                    builder = builder.with_defaults(::serde_json::Value::Object(defaults_json))?;
+                // Apply default values, including those of nested structs
+                let defaults: fn() -> ::gonfig::schema::__private::Value =
+                    || <Self as ::gonfig::schema::GonfigSchema>::gonfig_schema().defaults();
+                let builder = builder.with_defaults_deferred(defaults).with_schema(schema);
+
+                (builder, cli)
+            }
+        }
+    }
+}
+
+/// Reject shapes the derive cannot map to configuration keys and attribute
+/// combinations that would otherwise be ignored.
+fn check_attributes(opts: &GonfigOpts) -> darling::Result<()> {
+    let mut errors = darling::Error::accumulator();
+    match &opts.data {
+        darling::ast::Data::Struct(fields) if fields.style.is_struct() => {
+            check_fields(fields.iter(), &mut errors);
+        }
+        darling::ast::Data::Struct(fields) => {
+            let shape = if fields.is_unit() { "unit" } else { "tuple" };
+            errors.push(
+                darling::Error::custom(format!(
+                    "`Gonfig` cannot be derived for {} structs, the fields need names to map to keys",
+                    shape
+                ))
+                .with_span(&opts.ident),
+            );
+        }
+        darling::ast::Data::Enum(variants) => {
+            for v in variants {
+                match v.fields.style {
+                    darling::ast::Style::Struct => check_fields(v.fields.iter(), &mut errors),
+                    darling::ast::Style::Tuple if v.fields.len() > 1 => errors.push(
+                        darling::Error::custom(
+                            "`Gonfig` supports unit, newtype and struct variants, not tuple variants with several fields",
+                        )
+                        .with_span(&v.ident),
+                    ),
+                    _ => {}
                 }
+            }
+        }
+    }
+    errors.finish()
+}
 
-                // Build the final configuration with explicit type
-                builder.build::<Self>()
+/// Check the attributes of each field, and that explicit names and positional
+/// indices are not shared between fields.
+fn check_fields<'a>(
+    fields: impl Iterator<Item = &'a GonfigField>,
+    errors: &mut darling::error::Accumulator,
+) {
+    let mut env_names = HashMap::new();
+    let mut cli_names = HashMap::new();
+    let mut shorts = HashMap::new();
+    let mut positionals = HashMap::new();
+    let mut next_positional = 0;
+
+    for f in fields {
+        let field = f
+            .ident
+            .as_ref()
+            .map(|i| i.unraw().to_string())
+            .unwrap_or_default();
+
+        if let Some(span) = f.skipped() {
+            for option in f.options() {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "`skip` cannot be combined with `{}`, skipped fields are not read",
+                        option
+                    ))
+                    .with_span(&span),
+                );
             }
+            continue;
+        }
 
-            pub fn gonfig_builder() -> ::gonfig::ConfigBuilder {
-                let mut builder = ::gonfig::ConfigBuilder::new();
+        if let (Some(_), Some(path)) = (&f.default, &f.default_fn) {
+            errors.push(
+                darling::Error::custom("`default` and `default_fn` cannot both be set")
+                    .with_span(path),
+            );
+        }
+        if let (true, Some(env_name)) = (f.no_env, &f.env_name) {
+            errors.push(
+                darling::Error::custom("`env_name` has no effect on a `no_env` field")
+                    .with_span(&env_name.span()),
+            );
+        }
+        if f.positional.is_some() {
+            if let Some(short) = &f.short {
+                errors.push(
+                    darling::Error::custom("positional fields have no flag to take `short`")
+                        .with_span(&short.span()),
+                );
+            }
+            if let Some(cli_name) = &f.cli_name {
+                errors.push(
+                    darling::Error::custom("positional fields have no flag to take `cli_name`")
+                        .with_span(&cli_name.span()),
+                );
+            }
+            if f.subcommand {
+                errors.push(
+                    darling::Error::custom("a field cannot be both `subcommand` and `positional`")
+                        .with_span(&f.span()),
+                );
+            }
+        }
+        if let Some(RangeOpts {
+            min: Some(Number(min)),
+            max: Some(Number(max)),
+        }) = &f.range
+        {
+            if min > max {
+                errors.push(
+                    darling::Error::custom(format!("`range` min {} is above max {}", min, max))
+                        .with_span(&f.span()),
+                );
+            }
+        }
+        if let Some(LengthOpts {
+            min: Some(min),
+            max: Some(max),
+        }) = &f.length
+        {
+            if min > max {
+                errors.push(
+                    darling::Error::custom(format!("`length` min {} is above max {}", min, max))
+                        .with_span(&f.span()),
+                );
+            }
+        }
+
+        let mut unique = |seen: &mut HashMap<String, String>, option: &str, value: String, span| {
+            if let Some(other) = seen.insert(value.clone(), field.clone()) {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "duplicate `{} = {}`, also used by field `{}`",
+                        option, value, other
+                    ))
+                    .with_span(&span),
+                );
+            }
+        };
+        if let Some(name) = &f.env_name {
+            let value = format!("{:?}", name.as_str());
+            unique(&mut env_names, "env_name", value, name.span());
+        }
+        if let Some(name) = &f.cli_name {
+            let value = format!("{:?}", name.as_str());
+            unique(&mut cli_names, "cli_name", value, name.span());
+        }
+        if let Some(short) = &f.short {
+            let value = format!("{:?}", **short);
+            unique(&mut shorts, "short", value, short.span());
+        }
+        if let Some(index) = positional_index(f, &mut next_positional) {
+            unique(&mut positionals, "index", index.to_string(), f.span());
+        }
+    }
+}
 
-                // Regular field mappings: (field_name, env_key, cli_key)
-                let field_mappings: Vec<(String, String, String)> = vec![#(#regular_mappings),*];
+/// Check every literal `default` against its field's type.
+fn check_defaults(opts: &GonfigOpts) -> darling::Result<()> {
+    let fields: Vec<&GonfigField> = match &opts.data {
+        darling::ast::Data::Struct(fields) => fields.iter().collect(),
+        darling::ast::Data::Enum(variants) => {
+            variants.iter().flat_map(|v| v.fields.iter()).collect()
+        }
+    };
 
-                if #allow_env {
-                    // Create custom environment source with field mappings
-                    let mut env = ::gonfig::Environment::new();
+    let mut errors = darling::Error::accumulator();
+    for f in fields {
+        // The raw default of a field with a custom parser is the parser's input
+        if f.parse_with.is_some() {
+            continue;
+        }
+        if let Some(darling::util::Override::Explicit(raw)) = &f.default {
+            let one_of = f.one_of.as_ref().map(|list| list.0.as_slice());
+            if let Err(message) = defaults::check(raw, &f.ty, one_of) {
+                errors.push(darling::Error::custom(message).with_span(&raw.span()));
+            }
+        }
+    }
+    errors.finish()
+}
 
-                    if !#env_prefix.is_empty() {
-                        env = env.with_prefix(#env_prefix);
-                    }
+/// Check the literals of validation attributes: `regex` patterns must
+/// compile and `one_of` integers must fit the field.
+fn check_validators(opts: &GonfigOpts) -> darling::Result<()> {
+    let fields: Vec<&GonfigField> = match &opts.data {
+        darling::ast::Data::Struct(fields) => fields.iter().collect(),
+        darling::ast::Data::Enum(variants) => {
+            variants.iter().flat_map(|v| v.fields.iter()).collect()
+        }
+    };
 
-                    // Apply field-level mappings for regular fields
-                    for (field_name, env_key, _cli_key) in &field_mappings {
-                        env = env.with_field_mapping(field_name, env_key);
-                    }
+    let mut errors = darling::Error::accumulator();
+    for f in fields {
+        if let Some(pattern) = &f.regex {
+            if let Err(e) = regex::Regex::new(pattern) {
+                let message = format!("invalid regex pattern `{}`: {}", pattern.as_str(), e);
+                errors.push(darling::Error::custom(message).with_span(&pattern.span()));
+            }
+        }
+        let ints = f
+            .one_of
+            .iter()
+            .flat_map(|list| &list.0)
+            .filter_map(|lit| match lit {
+                syn::Lit::Int(i) => Some(i),
+                _ => None,
+            });
+        for lit in ints {
+            if let Err(message) = defaults::check_one_of_int(lit, &f.ty) {
+                errors.push(darling::Error::custom(message).with_span(lit));
+            }
+        }
+    }
+    errors.finish()
+}
 
-                    builder = builder.with_env_custom(env);
-                }
+/// Constant assertions that the string defaults of fields holding an enum
+/// deriving `Gonfig` name one of its unit variants. Fields of other types
+/// pass, see `__private::EnumVariants`.
+fn variant_default_checks(opts: &GonfigOpts) -> Vec<proc_macro2::TokenStream> {
+    // The field types of a generic type cannot be named in a constant
+    if opts.generics.type_params().next().is_some() {
+        return Vec::new();
+    }
 
-                if #allow_cli {
-                    // Create custom CLI source with field mappings
-                    let mut cli = ::gonfig::Cli::from_args();
+    let fields: Vec<&GonfigField> = match &opts.data {
+        darling::ast::Data::Struct(fields) => fields.iter().collect(),
+        darling::ast::Data::Enum(variants) => {
+            variants.iter().flat_map(|v| v.fields.iter()).collect()
+        }
+    };
 
-                    // Apply field-level CLI mappings for regular fields
-                    for (field_name, _env_key, cli_key) in &field_mappings {
-                        cli = cli.with_field_mapping(field_name, cli_key);
-                    }
+    fields
+        .into_iter()
+        .filter(|f| f.parse_with.is_none())
+        .filter_map(|f| {
+            let Some(darling::util::Override::Explicit(raw)) = &f.default else {
+                return None;
+            };
+            let (selector, ty) = defaults::variant_selector(raw, &f.ty)?;
+            let message = format!(
+                "invalid default `{}`: not a unit variant of `{}`",
+                raw.as_str(),
+                quote!(#ty).to_string().replace(' ', "")
+            );
+            Some(quote_spanned! {raw.span()=>
+                const _: () = {
+                    #[allow(unused_imports)]
+                    use ::gonfig::schema::__private::ViaPlainVariants as _;
+                    ::std::assert!(
+                        ::gonfig::schema::__private::is_variant(
+                            ::gonfig::schema::__private::SchemaProbe::<#ty>::VARIANTS,
+                            #selector,
+                        ),
+                        #message
+                    );
+                };
+            })
+        })
+        .collect()
+}
+
+/// Serialized name of an enum variant.
+fn variant_name(v: &GonfigVariant, serde_container: &SerdeContainer) -> String {
+    SerdeItem::from_attrs(&v.attrs).rename.unwrap_or_else(|| {
+        let ident = v.ident.unraw().to_string();
+        match serde_container.rename_all {
+            Some(rule) => rule.apply_to_variant(&ident),
+            None => ident,
+        }
+    })
+}
+
+/// Build the `ConfigFiles` expression for `config_file`, `config_file_env`
+/// and `config_file_cli`, or `None` when none of them is set.
+fn config_files_tokens(opts: &GonfigOpts) -> Option<proc_macro2::TokenStream> {
+    let cli_flag = config_cli_flag(opts);
+    if opts.config_file.is_none() && opts.config_file_env.is_none() && cli_flag.is_none() {
+        return None;
+    }
+
+    let paths = split_list(&opts.config_file);
+    let env_var = opts
+        .config_file_env
+        .as_ref()
+        .map(|e| quote! { .env_var(#e) });
+    let cli_flag = cli_flag.map(|c| quote! { .cli_flag(#c) });
+    let optional = opts.config_optional;
+
+    Some(quote! {
+        ::gonfig::ConfigFiles::new()
+            #(.path(#paths))*
+            #env_var
+            #cli_flag
+            .optional(#optional)
+    })
+}
+
+/// The flag naming config files: `config_file_cli`, or `config` with
+/// `allow_cli` when config files are read.
+fn config_cli_flag(opts: &GonfigOpts) -> Option<String> {
+    let reads_files =
+        opts.allow_config || opts.config_file.is_some() || opts.config_file_env.is_some();
+    opts.config_file_cli
+        .clone()
+        .or_else(|| (opts.allow_cli && reads_files).then(|| "config".to_string()))
+}
+
+/// Build the `FieldSchema` expressions of the fields that are not skipped.
+///
+/// Positional fields without an explicit index take the next one, in
+/// declaration order.
+fn schema_fields<'a>(
+    fields: impl Iterator<Item = &'a GonfigField>,
+    naming: &Naming,
+) -> Vec<proc_macro2::TokenStream> {
+    let mut next_positional = 0;
+    fields
+        .filter_map(|f| field_schema(f, positional_index(f, &mut next_positional), naming))
+        .collect()
+}
+
+/// How the fields of a struct or variant are named in each source.
+struct Naming {
+    /// serde's `rename_all` for the fields, giving their keys.
+    rename_all: Option<RenameRule>,
+    /// Casing of the environment variable segments, from `rename_all_env`.
+    env: RenameRule,
+    /// Casing of the CLI flag segments, from `rename_all_cli`.
+    cli: RenameRule,
+}
+
+impl Naming {
+    fn new(opts: &GonfigOpts, rename_all: Option<RenameRule>) -> Self {
+        Self {
+            rename_all,
+            env: opts.rename_all_env.unwrap_or(RenameRule::ScreamingSnake),
+            cli: opts.rename_all_cli.unwrap_or(RenameRule::Kebab),
+        }
+    }
+
+    /// The serialized name of a field: serde's `rename`, or the identifier
+    /// under `rename_all`.
+    fn key(&self, f: &GonfigField) -> Option<String> {
+        let ident = f.ident.as_ref()?.unraw().to_string();
+        Some(
+            SerdeItem::from_attrs(&f.attrs)
+                .rename
+                .unwrap_or_else(|| match self.rename_all {
+                    Some(rule) => rule.apply_to_field(&ident),
+                    None => ident,
+                }),
+        )
+    }
+}
+
+/// The index of a positional field, explicit or following the previous one.
+fn positional_index(f: &GonfigField, next: &mut usize) -> Option<usize> {
+    f.positional.as_ref().map(|p| {
+        let index = p.as_ref().explicit().and_then(|o| o.index);
+        let index = index.unwrap_or(*next);
+        *next = index + 1;
+        index
+    })
+}
+
+/// Build the `FieldSchema` expression for a field, or `None` when it is skipped.
+fn field_schema(
+    f: &GonfigField,
+    positional: Option<usize>,
+    naming: &Naming,
+) -> Option<proc_macro2::TokenStream> {
+    if f.skipped().is_some() {
+        return None;
+    }
 
-                    builder = builder.with_cli_custom(cli);
+    let field_str = naming.key(f)?;
+    // Segments are only spelled out when they differ from the schema's own
+    let env_segment = Some(naming.env.apply_to_name(&field_str))
+        .filter(|segment| *segment != field_str.to_uppercase())
+        .map(|segment| quote! { .env_segment(#segment) });
+    let cli_segment = Some(naming.cli.apply_to_name(&field_str))
+        .filter(|segment| *segment != field_str.replace('_', "-"))
+        .map(|segment| quote! { .cli_segment(#segment) });
+
+    let env_name = f
+        .env_name
+        .as_ref()
+        .map(|n| n.as_str())
+        .map(|n| quote! { .env_name(#n) });
+    let cli_name = f
+        .cli_name
+        .as_ref()
+        .map(|n| n.as_str())
+        .map(|n| quote! { .cli_name(#n) });
+    let ty = &f.ty;
+    let default = match (&f.default, &f.default_fn) {
+        (Some(darling::util::Override::Explicit(d)), _) => {
+            let d = d.as_str();
+            Some(quote! { .default_value(#d) })
+        }
+        (Some(darling::util::Override::Inherit), _) => Some(quote! {
+            .default_fn(|| ::gonfig::schema::__private::default_value(
+                <#ty as ::std::default::Default>::default()
+            ))
+        }),
+        (None, Some(path)) => Some(quote! {
+            .default_fn(|| ::gonfig::schema::__private::default_value(#path()))
+        }),
+        (None, None) => None,
+    };
+
+    let validators = field_validators(f);
+    let constraints = field_constraints(f);
+    let sources = field_sources(f);
+    let deprecated = field_deprecated_names(f);
+    let doc = doc_comment(&f.attrs).map(|doc| quote! { .doc(#doc) });
+    let short = f.short.map(|c| *c).map(|c| quote! { .short(#c) });
+    let sensitive = f.sensitive.then(|| quote! { .sensitive() });
+    let subcommand = f.subcommand.then(|| quote! { .subcommand() });
+    let positional = positional.map(|index| quote! { .positional(#index) });
+    let parser = f.parse_with.as_ref().map(|path| {
+        quote! {
+            .parser(|value| ::gonfig::schema::__private::parse_with(value, #path))
+        }
+    });
+    // A custom parser takes the raw string
+    let kind = match &f.parse_with {
+        Some(_) => syn::Ident::new("String", proc_macro2::Span::call_site()),
+        None => value_kind(&f.ty),
+    };
+
+    // Nested `Gonfig` types contribute their own schema, everything else is a
+    // leaf; an `Option` of one is an optional section
+    let optional = option_inner(&f.ty);
+    let nested = nested_schema_probe(optional.unwrap_or(&f.ty));
+    let optional = optional.map(|_| quote! { .optional() });
+
+    Some(quote! {
+        ::gonfig::schema::FieldSchema::new(#field_str)
+            #env_name
+            #cli_name
+            #env_segment
+            #cli_segment
+            #default
+            #(.validator(#validators))*
+            #(.constraint(#constraints))*
+            #sources
+            #(#deprecated)*
+            #doc
+            #short
+            #sensitive
+            #subcommand
+            #positional
+            #parser
+            #optional
+            .kind(::gonfig::schema::ValueKind::#kind)
+            .nested(#nested)
+    })
+}
+
+/// Build the `FieldValidator` expressions for a field's validation attributes.
+fn field_validators(f: &GonfigField) -> Vec<proc_macro2::TokenStream> {
+    let mut validators = Vec::new();
+
+    if let Some(range) = &f.range {
+        let min = option_tokens(range.min.map(|Number(n)| n));
+        let max = option_tokens(range.max.map(|Number(n)| n));
+        validators.push(quote! {
+            ::gonfig::validation::FieldValidator::Range { min: #min, max: #max }
+        });
+    }
+
+    if let Some(length) = &f.length {
+        let min = option_tokens(length.min);
+        let max = option_tokens(length.max);
+        validators.push(quote! {
+            ::gonfig::validation::FieldValidator::Length { min: #min, max: #max }
+        });
+    }
+
+    // Compiled once per field; `check_validators` made sure it compiles
+    if let Some(pattern) = &f.regex {
+        let pattern = pattern.as_str();
+        validators.push(quote! {
+            ::gonfig::validation::FieldValidator::Regex({
+                static REGEX: ::gonfig::schema::__private::Lazy<::gonfig::validation::Regex> =
+                    ::gonfig::schema::__private::Lazy::new(|| {
+                        ::gonfig::validation::Regex::new(#pattern).expect("checked by the derive")
+                    });
+                ::std::clone::Clone::clone(&*REGEX)
+            })
+        });
+    }
+
+    if let Some(allowed) = &f.one_of {
+        let values = allowed.0.iter().map(|lit| match lit {
+            // Integer literals are widened so large values don't overflow i32;
+            // `check_validators` has made sure they fit in 64 bits
+            syn::Lit::Int(i) => match i.base10_parse::<i64>() {
+                Ok(n) => quote! { ::gonfig::schema::__private::Value::from(#n) },
+                Err(_) => {
+                    let n = i.base10_parse::<u64>().unwrap_or_default();
+                    quote! { ::gonfig::schema::__private::Value::from(#n) }
                 }
+            },
+            _ => quote! { ::gonfig::schema::__private::Value::from(#lit) },
+        });
+        validators.push(quote! {
+            ::gonfig::validation::FieldValidator::OneOf(vec![#(#values),*])
+        });
+    }
+
+    if f.url {
+        validators.push(quote! { ::gonfig::validation::FieldValidator::Url });
+    }
 
-                // Note: Config file loading and defaults are not supported in gonfig_builder()
-                // due to Result handling requirements. Use from_gonfig_with_builder() instead
-                // for full config file and default value support.
+    if f.non_empty {
+        validators.push(quote! { ::gonfig::validation::FieldValidator::NonEmpty });
+    }
 
-                builder
-            }
+    if let Some(custom) = &f.custom {
+        let ty = &f.ty;
+        validators.push(quote! {
+            ::gonfig::validation::FieldValidator::Custom(|value| {
+                let typed: #ty = ::gonfig::schema::__private::typed_field(value)?;
+                #custom(&typed).map_err(|e| e.to_string())
+            })
+        });
+    }
+
+    validators
+}
+
+/// Build the `.sources(...)` call for `sources = "..."` and `no_env`, if any.
+fn field_sources(f: &GonfigField) -> Option<proc_macro2::TokenStream> {
+    if f.sources.is_none() && !f.no_env {
+        return None;
+    }
+
+    let mut sources = f
+        .sources
+        .clone()
+        .map(|SourceList(s)| s)
+        .unwrap_or_else(|| vec!["Environment", "ConfigFile", "Cli"]);
+    if f.no_env {
+        sources.retain(|s| *s != "Environment");
+    }
+
+    let sources = sources
+        .iter()
+        .map(|s| syn::Ident::new(s, proc_macro2::Span::call_site()));
+    Some(quote! { .sources([#(::gonfig::Source::#sources),*]) })
+}
+
+/// Build the `FieldConstraint` expressions for `required_if`, `conflicts_with`
+/// and `requires`. The latter two accept comma-separated field names.
+fn field_constraints(f: &GonfigField) -> Vec<proc_macro2::TokenStream> {
+    let mut constraints = Vec::new();
+
+    if let Some(condition) = &f.required_if {
+        constraints.push(quote! {
+            ::gonfig::validation::FieldConstraint::RequiredIf(#condition)
+        });
+    }
+
+    for other in split_list(&f.conflicts_with) {
+        constraints.push(quote! {
+            ::gonfig::validation::FieldConstraint::ConflictsWith(#other)
+        });
+    }
+
+    for other in split_list(&f.requires) {
+        constraints.push(quote! {
+            ::gonfig::validation::FieldConstraint::Requires(#other)
+        });
+    }
+
+    constraints
+}
+
+/// Build the `.alias(...)`, `.deprecated_env(...)` and `.deprecated_key(...)`
+/// calls for a field's former names.
+fn field_deprecated_names(f: &GonfigField) -> Vec<proc_macro2::TokenStream> {
+    let aliases = split_list(&f.alias);
+    let envs = split_list(&f.deprecated_env);
+    let keys = split_list(&f.deprecated_key);
+
+    aliases
+        .iter()
+        .map(|a| quote! { .alias(#a) })
+        .chain(envs.iter().map(|e| quote! { .deprecated_env(#e) }))
+        .chain(keys.iter().map(|k| quote! { .deprecated_key(#k) }))
+        .collect()
+}
+
+/// Join the `///` lines of an item, keeping blank lines as paragraph breaks.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }),
+                ..
+            }) => Some(s.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Classify a field type for CLI parsing, looking through `Option<T>`.
+fn value_kind(ty: &syn::Type) -> syn::Ident {
+    let kind = match last_segment(ty) {
+        Some(segment) if segment.ident == "Option" => {
+            return match generic_argument(segment) {
+                Some(inner) => value_kind(inner),
+                None => syn::Ident::new("Any", proc_macro2::Span::call_site()),
+            };
+        }
+        Some(segment) => match segment.ident.to_string().as_str() {
+            "bool" => "Bool",
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" => "Integer",
+            "f32" | "f64" => "Float",
+            "String" | "char" => "String",
+            "PathBuf" => "Path",
+            "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => "List",
+            _ => "Any",
+        },
+        None => "Any",
+    };
+    syn::Ident::new(kind, proc_macro2::Span::call_site())
+}
+
+/// The `T` of an `Option<T>` field type.
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    last_segment(ty)
+        .filter(|segment| segment.ident == "Option")
+        .and_then(generic_argument)
+}
+
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    match ty {
+        syn::Type::Path(path) => path.path.segments.last(),
+        syn::Type::Group(group) => last_segment(&group.elem),
+        _ => None,
+    }
+}
+
+fn generic_argument(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Split a comma-separated attribute value into trimmed, non-empty names.
+fn split_list(list: &Option<String>) -> Vec<String> {
+    list.iter()
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn option_tokens<T: quote::ToTokens>(value: Option<T>) -> proc_macro2::TokenStream {
+    match value {
+        Some(value) => quote! { ::std::option::Option::Some(#value) },
+        None => quote! { ::std::option::Option::None },
+    }
+}
+
+/// Expression evaluating to `Some(schema)` when `ty` derives `Gonfig` and `None` otherwise.
+fn nested_schema_probe(ty: &syn::Type) -> proc_macro2::TokenStream {
+    quote! {
+        {
+            #[allow(unused_imports)]
+            use ::gonfig::schema::__private::{ViaPlain as _, ViaSchema as _};
+            (&&::gonfig::schema::__private::SchemaProbe::<#ty>::new()).nested_schema()
         }
     }
 }
@@ -0,0 +1,217 @@
+//! Minimal reader for the `#[serde(...)]` attributes that affect serialized names.
+
+use syn::{Attribute, LitStr};
+
+/// Container-level serde attributes.
+#[derive(Debug, Default)]
+pub(crate) struct SerdeContainer {
+    pub rename_all: Option<RenameRule>,
+    pub rename_all_fields: Option<RenameRule>,
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub untagged: bool,
+}
+
+/// Field- or variant-level serde attributes.
+#[derive(Debug, Default)]
+pub(crate) struct SerdeItem {
+    pub rename: Option<String>,
+    /// `rename_all` on a variant, renaming the variant's fields.
+    pub rename_all: Option<RenameRule>,
+}
+
+/// Casing rules accepted by serde's `rename_all`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_str(rule: &str) -> Option<Self> {
+        match rule {
+            "lowercase" => Some(Self::Lower),
+            "UPPERCASE" => Some(Self::Upper),
+            "PascalCase" => Some(Self::Pascal),
+            "camelCase" => Some(Self::Camel),
+            "snake_case" => Some(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Some(Self::ScreamingSnake),
+            "kebab-case" => Some(Self::Kebab),
+            "SCREAMING-KEBAB-CASE" => Some(Self::ScreamingKebab),
+            _ => None,
+        }
+    }
+
+    /// Rename a field written in snake_case.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+            Self::Camel => {
+                let pascal = Self::Pascal.apply_to_field(field);
+                match pascal.chars().next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + &pascal[1..],
+                    None => pascal,
+                }
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Rename a serialized name in any casing, splitting it into words at
+    /// `_`, `-` and lower-to-upper case changes.
+    pub fn apply_to_name(self, name: &str) -> String {
+        if !name.contains('-') && !name.chars().any(|c| c.is_ascii_uppercase()) {
+            return self.apply_to_field(name);
+        }
+
+        let mut snake = String::new();
+        let mut previous: Option<char> = None;
+        let mut chars = name.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if ch == '_' || ch == '-' {
+                if !snake.is_empty() && !snake.ends_with('_') {
+                    snake.push('_');
+                }
+            } else {
+                // `dbUrl` and `HTTPServer` both break before the upper-case letter
+                let next_lower = chars.peek().is_some_and(|c| c.is_ascii_lowercase());
+                let boundary = previous.is_some_and(|p| {
+                    p.is_ascii_lowercase()
+                        || p.is_ascii_digit()
+                        || (p.is_ascii_uppercase() && next_lower)
+                });
+                if ch.is_ascii_uppercase() && boundary && !snake.ends_with('_') {
+                    snake.push('_');
+                }
+                snake.push(ch.to_ascii_lowercase());
+            }
+            previous = Some(ch);
+        }
+        self.apply_to_field(&snake)
+    }
+
+    /// Rename a variant written in PascalCase.
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_string(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => variant[..1].to_ascii_lowercase() + &variant[1..],
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+}
+
+/// `#[Gonfig(rename_all_env = "...")]` and `rename_all_cli` take the same
+/// rules as serde's `rename_all`.
+impl darling::FromMeta for RenameRule {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Self::from_str(value).ok_or_else(|| darling::Error::unknown_value(value))
+    }
+}
+
+impl SerdeContainer {
+    pub fn from_attrs(attrs: &[Attribute]) -> Self {
+        let mut container = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            // Unknown serde options are serde's business, not ours
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    container.rename_all =
+                        deserialize_name(&meta)?.and_then(|r| RenameRule::from_str(&r));
+                } else if meta.path.is_ident("rename_all_fields") {
+                    container.rename_all_fields =
+                        deserialize_name(&meta)?.and_then(|r| RenameRule::from_str(&r));
+                } else if meta.path.is_ident("tag") {
+                    container.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("content") {
+                    container.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("untagged") {
+                    container.untagged = true;
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            });
+        }
+        container
+    }
+}
+
+impl SerdeItem {
+    pub fn from_attrs(attrs: &[Attribute]) -> Self {
+        let mut item = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    item.rename = deserialize_name(&meta)?;
+                } else if meta.path.is_ident("rename_all") {
+                    item.rename_all =
+                        deserialize_name(&meta)?.and_then(|r| RenameRule::from_str(&r));
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            });
+        }
+        item
+    }
+}
+
+/// Read `name = "..."` or the `deserialize` half of `name(serialize = "...", deserialize = "...")`.
+fn deserialize_name(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        let value = inner.value()?.parse::<LitStr>()?.value();
+        if inner.path.is_ident("deserialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+fn skip_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| skip_value(&inner))?;
+    }
+    Ok(())
+}
@@ -2,18 +2,23 @@
 
 use crate::{
     cli::Cli,
-    config::{Config, ConfigFormat},
+    config::{Config, ConfigFiles, ConfigFormat},
     environment::Environment,
     error::{Error, Result},
     merge::{ConfigMerger, MergeStrategy},
-    source::ConfigSource,
+    path,
+    schema::{FieldMapping, SourceRestriction, StructSchema},
+    source::{ConfigSource, Source},
+    validation::{Constraint, Diagnostics, ValueOrigin},
 };
+use once_cell::sync::OnceCell;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::any::Any;
 use std::path::Path;
 
 /// Type alias for validation functions to reduce complexity.
-type ValidationFn = Box<dyn Fn(&Value) -> Result<()>>;
+type ValidationFn = Box<dyn Fn(&Value, &mut Diagnostics) -> Result<()>>;
 
 /// Builder for assembling configuration from multiple sources.
 ///
@@ -44,7 +49,11 @@ type ValidationFn = Box<dyn Fn(&Value) -> Result<()>>;
 pub struct ConfigBuilder {
     sources: Vec<Box<dyn ConfigSource>>,
     merge_strategy: MergeStrategy,
-    validate: Option<ValidationFn>,
+    validators: Vec<ValidationFn>,
+    typed_validators: Vec<ValidationFn>,
+    constraints: Vec<Constraint>,
+    deny_deprecated: bool,
+    schema: Option<StructSchema>,
 }
 
 impl Default for ConfigBuilder {
@@ -59,7 +68,11 @@ impl ConfigBuilder {
         Self {
             sources: Vec::new(),
             merge_strategy: MergeStrategy::Deep,
-            validate: None,
+            validators: Vec::new(),
+            typed_validators: Vec::new(),
+            constraints: Vec::new(),
+            deny_deprecated: false,
+            schema: None,
         }
     }
 
@@ -197,6 +210,72 @@ impl ConfigBuilder {
         Ok(self.add_source(Box::new(config)))
     }
 
+    /// Add the configuration files described by `files`.
+    ///
+    /// See [`ConfigFiles`] for how the paths are chosen. The CLI flag, if any,
+    /// is read from `std::env::args()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if a required file does not exist, or any
+    /// error of [`with_file`] if a file cannot be read or parsed.
+    ///
+    /// [`with_file`]: ConfigBuilder::with_file
+    pub fn with_config_files(self, files: &ConfigFiles) -> Result<Self> {
+        self.with_config_files_from(files, &Cli::from_args())
+    }
+
+    /// Add the configuration files described by `files`, reading the CLI flag
+    /// from already parsed arguments.
+    ///
+    /// Behaves like [`with_config_files`](ConfigBuilder::with_config_files).
+    pub fn with_config_files_from(mut self, files: &ConfigFiles, cli: &Cli) -> Result<Self> {
+        for (path, required) in files.resolve(cli) {
+            if required || path.exists() {
+                self = self.add_source(Box::new(config_file(&path, required)?));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Add a required configuration file that is read when the configuration
+    /// is built.
+    ///
+    /// Unlike [`with_file`](ConfigBuilder::with_file), this cannot fail:
+    /// a missing or invalid file is reported by [`build`](ConfigBuilder::build).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::ConfigBuilder;
+    ///
+    /// let builder = ConfigBuilder::new().with_file_deferred("missing.toml");
+    /// assert!(builder.build_value().is_err());
+    /// ```
+    pub fn with_file_deferred(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        self.add_source_deferred(Source::ConfigFile, move || {
+            Ok(Box::new(Config::from_file(&path)?))
+        })
+    }
+
+    /// Add the configuration files described by `files`, reading them when
+    /// the configuration is built.
+    ///
+    /// The paths are chosen as by [`with_config_files_from`], but a required
+    /// file that is missing or a file that cannot be parsed is reported by
+    /// [`build`](ConfigBuilder::build).
+    ///
+    /// [`with_config_files_from`]: ConfigBuilder::with_config_files_from
+    pub fn with_config_files_deferred(mut self, files: &ConfigFiles, cli: &Cli) -> Self {
+        for (path, required) in files.resolve(cli) {
+            self = self.add_source_deferred(Source::ConfigFile, move || {
+                Ok(Box::new(config_file(&path, required)?))
+            });
+        }
+        self
+    }
+
     /// Add a configuration file with explicit format.
     ///
     /// Use this method when you need to override the automatic format detection
@@ -219,9 +298,9 @@ impl ConfigBuilder {
 
     /// Add CLI arguments from `std::env::args()`.
     ///
-    /// This creates a basic CLI source that parses arguments in the format:
-    /// - `--key=value` or `--key value`
-    /// - `-k=value` or `-k value` (for single character keys)
+    /// This creates a basic CLI source that parses GNU-style arguments such as
+    /// `--key=value`, `--key value`, `--no-flag`, `-k value` and `-vvv`; see
+    /// [`Cli::from_vec`] for the full syntax.
     ///
     /// For more advanced CLI parsing with clap, use [`with_clap`] instead.
     ///
@@ -242,8 +321,17 @@ impl ConfigBuilder {
     }
 
     /// Add a custom CLI configuration.
+    ///
+    /// Values of clap defaults held by `cli`, see [`Cli::clap_defaults`], are
+    /// added with the priority of [`with_defaults`](Self::with_defaults).
     pub fn with_cli_custom(self, cli: Cli) -> Self {
-        self.add_source(Box::new(cli))
+        let clap_defaults = cli.clap_defaults();
+        let builder = self.add_source(Box::new(cli));
+        if clap_defaults.as_object().is_some_and(|d| !d.is_empty()) {
+            builder.push_defaults(clap_defaults)
+        } else {
+            builder
+        }
     }
 
     /// Add CLI arguments using clap parser.
@@ -251,6 +339,10 @@ impl ConfigBuilder {
     /// This method integrates with clap's derive API for advanced CLI argument parsing.
     /// Your struct must implement both `clap::Parser` and `serde::Serialize`.
     ///
+    /// Only arguments the user passed override other sources; values of clap
+    /// `default_value`s are applied as defaults and options left unset are
+    /// ignored.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -273,7 +365,7 @@ impl ConfigBuilder {
     /// ```
     pub fn with_clap<T: clap::Parser + serde::Serialize>(self) -> Result<Self> {
         let cli = Cli::with_clap_app::<T>()?;
-        Ok(self.add_source(Box::new(cli)))
+        Ok(self.with_cli_custom(cli))
     }
 
     /// Add default values as a fallback configuration source.
@@ -299,43 +391,74 @@ impl ConfigBuilder {
     ///     .with_defaults(defaults)?;
     /// # Ok::<(), gonfig::Error>(())
     /// ```
-    pub fn with_defaults(mut self, defaults: Value) -> Result<Self> {
-        // Create a custom source for defaults with lowest priority
-        struct DefaultsSource {
-            value: Value,
-        }
-
-        impl ConfigSource for DefaultsSource {
-            fn collect(&self) -> Result<Value> {
-                Ok(self.value.clone())
-            }
-
-            fn source_type(&self) -> crate::source::Source {
-                // Use Default source type which has the lowest priority
-                crate::source::Source::Default
-            }
-
-            fn has_value(&self, key: &str) -> bool {
-                self.value.get(key).is_some()
-            }
-
-            fn get_value(&self, key: &str) -> Option<Value> {
-                self.value.get(key).cloned()
-            }
+    pub fn with_defaults(self, defaults: Value) -> Result<Self> {
+        Ok(self.push_defaults(defaults))
+    }
 
-            fn as_any(&self) -> &dyn std::any::Any {
-                self
-            }
-        }
+    /// Add default values computed when the configuration is built.
+    ///
+    /// Behaves like [`with_defaults`](ConfigBuilder::with_defaults). The
+    /// generated `gonfig_builder()` uses it for the defaults of the schema.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::ConfigBuilder;
+    ///
+    /// let value = ConfigBuilder::new()
+    ///     .with_defaults_deferred(|| serde_json::json!({ "port": 8080 }))
+    ///     .build_value()?;
+    /// assert_eq!(value["port"], 8080);
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn with_defaults_deferred<F>(mut self, defaults: F) -> Self
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        let source = Deferred::new(Source::Default, move || {
+            Ok(Box::new(DefaultsSource { value: defaults() }))
+        });
+        self.sources.insert(0, Box::new(source));
+        self
+    }
 
+    fn push_defaults(mut self, defaults: Value) -> Self {
         // Add defaults as the first source (lowest priority)
         self.sources
             .insert(0, Box::new(DefaultsSource { value: defaults }));
-        Ok(self)
+        self
+    }
+
+    /// Add a source created by `load` when the configuration is built.
+    ///
+    /// An error from `load` is returned by [`build`](ConfigBuilder::build),
+    /// so a builder can be set up without handling errors first. The source
+    /// is created once and takes its place among the sources by
+    /// `source_type`, like one added with [`add_source`](Self::add_source).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigBuilder, ConfigSource, Error, Source};
+    ///
+    /// let builder = ConfigBuilder::new().add_source_deferred(Source::Cli, || {
+    ///     Err(Error::Cli("unexpected argument '--verbose'".to_string()))
+    /// });
+    /// let err = builder.build_value().unwrap_err();
+    /// assert!(err.to_string().contains("--verbose"));
+    /// ```
+    pub fn add_source_deferred<F>(self, source_type: Source, load: F) -> Self
+    where
+        F: Fn() -> Result<Box<dyn ConfigSource>> + Send + Sync + 'static,
+    {
+        self.add_source(Box::new(Deferred::new(source_type, load)))
     }
 
     /// Add a validation function that will be called on the final merged configuration.
     ///
+    /// Validators run before deserialization. Any number of validators can be
+    /// registered; all of them run and their errors are reported together.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -355,7 +478,188 @@ impl ConfigBuilder {
     where
         F: Fn(&Value) -> Result<()> + 'static,
     {
-        self.validate = Some(Box::new(validator));
+        self.validators
+            .push(Box::new(move |value, _: &mut Diagnostics| validator(value)));
+        self
+    }
+
+    /// Add a validation function over the deserialized configuration.
+    ///
+    /// Typed validators run after the merged configuration has been
+    /// deserialized into `T`, so they can use the config struct directly.
+    /// All typed validators run and their errors are reported together.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{ConfigBuilder, Error};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     min_workers: u32,
+    ///     max_workers: u32,
+    /// }
+    ///
+    /// let builder = ConfigBuilder::new()
+    ///     .validate_typed(|config: &Config| {
+    ///         if config.min_workers > config.max_workers {
+    ///             return Err(Error::Validation("min_workers exceeds max_workers".into()));
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    pub fn validate_typed<T, F>(self, validator: F) -> Self
+    where
+        T: DeserializeOwned,
+        F: Fn(&T) -> Result<()> + 'static,
+    {
+        self.diagnose_typed(move |config: &T, _: &mut Diagnostics| validator(config))
+    }
+
+    /// Add a typed validator that can report warnings as well as errors.
+    ///
+    /// Errors recorded in [`Diagnostics`] fail the build. Warnings are returned
+    /// by [`build_with_warnings`] and logged through `tracing` by [`build`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::ConfigBuilder;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     debug: bool,
+    /// }
+    ///
+    /// let builder = ConfigBuilder::new()
+    ///     .diagnose_typed(|config: &Config, diagnostics| {
+    ///         if config.debug {
+    ///             diagnostics.warn("debug mode is enabled");
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    ///
+    /// [`build`]: ConfigBuilder::build
+    /// [`build_with_warnings`]: ConfigBuilder::build_with_warnings
+    pub fn diagnose_typed<T, F>(mut self, validator: F) -> Self
+    where
+        T: DeserializeOwned,
+        F: Fn(&T, &mut Diagnostics) -> Result<()> + 'static,
+    {
+        self.typed_validators
+            .push(Box::new(move |value, diagnostics| {
+                let config: T = serde_json::from_value(value.clone()).map_err(|e| {
+                    Error::Serialization(format!("Failed to deserialize config: {}", e))
+                })?;
+                validator(&config, diagnostics)
+            }));
+        self
+    }
+
+    /// Require `field` whenever `condition` holds in the merged configuration.
+    ///
+    /// `condition` is either a dotted path, which holds when that field is set
+    /// and not `false`, or `path=value`, which holds when that field equals
+    /// `value`. Equivalent to `#[gonfig(required_if = "...")]`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{ConfigBuilder, Error};
+    /// use serde_json::json;
+    ///
+    /// let result = ConfigBuilder::new()
+    ///     .with_defaults(json!({ "enable_tls": true }))?
+    ///     .required_if("tls_cert", "enable_tls")
+    ///     .build_value();
+    ///
+    /// assert!(matches!(result, Err(Error::Validation(_))));
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn required_if(mut self, field: impl Into<String>, condition: impl Into<String>) -> Self {
+        self.constraints.push(Constraint::RequiredIf {
+            field: field.into(),
+            condition: condition.into(),
+        });
+        self
+    }
+
+    /// Reject configurations that set both `field` and `other`.
+    ///
+    /// Values that only come from defaults do not count. Equivalent to
+    /// `#[gonfig(conflicts_with = "...")]`.
+    pub fn conflicts_with(mut self, field: impl Into<String>, other: impl Into<String>) -> Self {
+        self.constraints.push(Constraint::ConflictsWith {
+            field: field.into(),
+            other: other.into(),
+        });
+        self
+    }
+
+    /// Require `other` whenever `field` is set. Equivalent to
+    /// `#[gonfig(requires = "...")]`.
+    pub fn requires(mut self, field: impl Into<String>, other: impl Into<String>) -> Self {
+        self.constraints.push(Constraint::Requires {
+            field: field.into(),
+            other: other.into(),
+        });
+        self
+    }
+
+    /// Fail the build when a deprecated name is used.
+    ///
+    /// Former names declared with `alias`, `deprecated_env` and
+    /// `deprecated_key` are read when the current name is absent and, by
+    /// default, only produce a warning. In strict mode every use of a former
+    /// name is an error instead, so deployments can be checked before the
+    /// old names are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::ConfigBuilder;
+    ///
+    /// let strict = std::env::var("CI").is_ok();
+    /// let builder = ConfigBuilder::new().deny_deprecated(strict);
+    /// ```
+    pub fn deny_deprecated(mut self, deny: bool) -> Self {
+        self.deny_deprecated = deny;
+        self
+    }
+
+    /// Attach the schema of the target type.
+    ///
+    /// The schema is applied to the merged configuration before validation,
+    /// e.g. to match enum variant names case-insensitively. The derive macro
+    /// sets it automatically; use [`GonfigSchema::gonfig_schema`] to attach it
+    /// to a hand-assembled builder.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{ConfigBuilder, Gonfig, GonfigSchema};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, Gonfig)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Storage {
+    ///     Local,
+    ///     S3,
+    /// }
+    ///
+    /// let storage: Storage = ConfigBuilder::new()
+    ///     .with_schema(Storage::gonfig_schema())
+    ///     .with_defaults(serde_json::json!("LOCAL"))?
+    ///     .build()?;
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    ///
+    /// [`GonfigSchema::gonfig_schema`]: crate::GonfigSchema::gonfig_schema
+    pub fn with_schema(mut self, schema: StructSchema) -> Self {
+        self.schema = Some(schema);
         self
     }
 
@@ -394,47 +698,410 @@ impl ConfigBuilder {
     ///
     /// Returns an error if:
     /// - Any required configuration source fails to load
-    /// - Validation fails
+    /// - Validation fails, including the field validators of an attached schema
     /// - The final merged configuration cannot be deserialized into type `T`
     pub fn build<T: DeserializeOwned>(self) -> Result<T> {
-        let merger = ConfigMerger::new(self.merge_strategy);
+        let (config, warnings) = self.build_with_warnings()?;
+        for warning in warnings {
+            tracing::warn!("Configuration warning: {}", warning);
+        }
+        Ok(config)
+    }
+
+    /// Build the final configuration and return the validators' warnings.
+    ///
+    /// Behaves like [`build`], but instead of logging the warnings reported
+    /// through [`Diagnostics::warn`] it returns them alongside the configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::ConfigBuilder;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     debug: bool,
+    /// }
+    ///
+    /// let (config, warnings) = ConfigBuilder::new()
+    ///     .with_defaults(serde_json::json!({ "debug": true }))?
+    ///     .diagnose_typed(|config: &Config, diagnostics| {
+    ///         if config.debug {
+    ///             diagnostics.warn("debug mode is enabled");
+    ///         }
+    ///         Ok(())
+    ///     })
+    ///     .build_with_warnings::<Config>()?;
+    ///
+    /// assert_eq!(warnings, vec!["debug mode is enabled".to_string()]);
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    ///
+    /// [`build`]: ConfigBuilder::build
+    pub fn build_with_warnings<T: DeserializeOwned>(self) -> Result<(T, Vec<String>)> {
+        let mut diagnostics = Diagnostics::default();
+        let layers = self.collect_layers(&mut diagnostics)?;
+        let merged = self.merge_layers(&layers, &mut diagnostics)?;
+
+        let config = serde_json::from_value(merged.clone())
+            .map_err(|e| Error::Serialization(format!("Failed to deserialize config: {}", e)))?;
+
+        self.check_deserialized(&merged, &layers, &mut diagnostics)?;
+
+        Ok((config, diagnostics.into_warnings()))
+    }
 
-        let mut source_values = Vec::new();
-        for source in &self.sources {
-            let value = source.collect()?;
-            let priority = source.source_type().priority();
-            source_values.push((value, priority));
+    pub fn build_value(self) -> Result<Value> {
+        let mut diagnostics = Diagnostics::default();
+        let layers = self.collect_layers(&mut diagnostics)?;
+        let merged = self.merge_layers(&layers, &mut diagnostics)?;
+
+        self.check_deserialized(&merged, &layers, &mut diagnostics)?;
+
+        for warning in diagnostics.into_warnings() {
+            tracing::warn!("Configuration warning: {}", warning);
         }
 
-        let merged = merger.merge_sources(source_values);
+        Ok(merged)
+    }
 
-        if let Some(validator) = &self.validate {
-            validator(&merged)?;
+    /// Build the merged configuration and serialize it in `format`, with the
+    /// values of sensitive fields of the schema replaced by `"<redacted>"`.
+    ///
+    /// With a schema, only its fields are printed; other keys set by the
+    /// sources, such as unrelated environment variables with the prefix or
+    /// former names of fields, are left out.
+    ///
+    /// This is what `--print-config` prints for types deriving `Gonfig`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{ConfigBuilder, ConfigFormat, Gonfig, GonfigSchema};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Gonfig)]
+    /// struct Config {
+    ///     user: String,
+    ///     #[gonfig(sensitive)]
+    ///     password: String,
+    /// }
+    ///
+    /// let printed = ConfigBuilder::new()
+    ///     .with_defaults(serde_json::json!({ "user": "admin", "password": "hunter2" }))?
+    ///     .with_schema(Config::gonfig_schema())
+    ///     .render_config(ConfigFormat::Toml)?;
+    /// assert_eq!(printed, "password = \"<redacted>\"\nuser = \"admin\"\n");
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn render_config(self, format: ConfigFormat) -> Result<String> {
+        let schema = self.schema.clone();
+        let mut merged = self.build_value()?;
+        if let Some(schema) = schema {
+            schema.retain_fields(&mut merged);
+            schema.redact(&mut merged);
         }
+        format.serialize(&merged)
+    }
+
+    /// Collect every source as `(source index, value, priority)`.
+    ///
+    /// Values for fields that the schema does not allow from a source are
+    /// dropped with a warning.
+    fn collect_layers(&self, diagnostics: &mut Diagnostics) -> Result<Vec<(usize, Value, u8)>> {
+        let restrictions = self
+            .schema
+            .as_ref()
+            .map(StructSchema::source_restrictions)
+            .unwrap_or_default();
+        let mappings: Vec<FieldMapping> = self
+            .schema
+            .as_ref()
+            .map(StructSchema::field_mappings)
+            .unwrap_or_default();
+
+        let mut layers = Vec::new();
+        for (index, source) in self.sources.iter().enumerate() {
+            let mut value = source.collect()?;
+            let source_type = source.source_type();
+
+            if let Some(cli) = source.as_any().downcast_ref::<Cli>() {
+                cli.count_flags(&mut value, &mappings);
+            }
+            for mapping in mappings.iter().filter(|m| !m.deprecated.is_empty()) {
+                self.apply_deprecated_names(source.as_ref(), &mut value, mapping, diagnostics);
+            }
 
-        serde_json::from_value(merged)
-            .map_err(|e| Error::Serialization(format!("Failed to deserialize config: {}", e)))
+            if source_type != Source::Default {
+                for restriction in &restrictions {
+                    if restriction.allowed.contains(&source_type) {
+                        continue;
+                    }
+                    let origin = source.origin(&restriction.path);
+                    if path::remove(&mut value, &restriction.path).is_some() {
+                        diagnostics.warn(restriction_warning(
+                            &restriction.path,
+                            &origin.unwrap_or_else(|| source_type.to_string()),
+                            restriction,
+                        ));
+                    }
+                }
+            }
+
+            layers.push((index, value, source_type.priority()));
+        }
+        Ok(layers)
     }
 
-    pub fn build_value(self) -> Result<Value> {
+    fn merge_layers(
+        &self,
+        layers: &[(usize, Value, u8)],
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Value> {
         let merger = ConfigMerger::new(self.merge_strategy);
 
-        let mut source_values = Vec::new();
-        for source in &self.sources {
-            let value = source.collect()?;
-            let priority = source.source_type().priority();
-            source_values.push((value, priority));
-        }
+        let source_values = layers
+            .iter()
+            .map(|(_, value, priority)| (value.clone(), *priority))
+            .collect();
+        let mut merged = merger.merge_sources(source_values);
+        self.apply_indexed_overrides(layers, &mut merged, diagnostics)?;
 
-        let merged = merger.merge_sources(source_values);
+        if let Some(schema) = &self.schema {
+            schema.assemble_subcommands(&mut merged, self.selected_subcommand(layers))?;
+            schema.section_defaults(&mut merged);
+            schema.normalize(&mut merged)?;
+            for mut error in schema.parse_fields(&mut merged) {
+                error.origin = self.origin_of(layers, &error.path);
+                diagnostics.push_error(Error::Validation(error.to_string()));
+            }
+        }
 
-        if let Some(validator) = &self.validate {
-            validator(&merged)?;
+        for validator in &self.validators {
+            if let Err(e) = validator(&merged, diagnostics) {
+                diagnostics.push_error(e);
+            }
         }
+        self.check_constraints(&merged, layers, diagnostics);
+        diagnostics.check()?;
 
         Ok(merged)
     }
 
+    /// The subcommand given to the highest-priority CLI source, if any.
+    fn selected_subcommand(&self, layers: &[(usize, Value, u8)]) -> Option<&str> {
+        layers
+            .iter()
+            .rev()
+            .filter_map(|(index, _, _)| self.sources[*index].as_any().downcast_ref::<Cli>())
+            .find_map(Cli::subcommand)
+    }
+
+    /// Apply the `--set` overrides of CLI sources that index into arrays, e.g.
+    /// `servers[0].port`, which need the merged value to build upon.
+    fn apply_indexed_overrides(
+        &self,
+        layers: &[(usize, Value, u8)],
+        merged: &mut Value,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<()> {
+        let restrictions = self
+            .schema
+            .as_ref()
+            .map(StructSchema::source_restrictions)
+            .unwrap_or_default();
+
+        for (index, _, _) in layers {
+            let Some(cli) = self.sources[*index].as_any().downcast_ref::<Cli>() else {
+                continue;
+            };
+
+            for (set_path, value) in cli.set_overrides()? {
+                if !path::has_index(&set_path) {
+                    continue;
+                }
+
+                let field = path::without_indices(&set_path);
+                let restricted = restrictions.iter().find(|r| {
+                    !r.allowed.contains(&Source::Cli)
+                        && (field == r.path || field.starts_with(&format!("{}.", r.path)))
+                });
+                if let Some(restriction) = restricted {
+                    diagnostics.warn(restriction_warning(
+                        &set_path,
+                        &format!("CLI flag --set {}", set_path),
+                        restriction,
+                    ));
+                    continue;
+                }
+
+                path::set_indexed(merged, &set_path, value).map_err(Error::Cli)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the schema's field validators and the typed validators, reporting
+    /// every failure at once.
+    fn check_deserialized(
+        &self,
+        merged: &Value,
+        layers: &[(usize, Value, u8)],
+        diagnostics: &mut Diagnostics,
+    ) -> Result<()> {
+        if let Some(schema) = &self.schema {
+            for mut error in schema.validate(merged) {
+                error.origin = self.origin_of(layers, &error.path);
+                diagnostics.push_error(Error::Validation(error.to_string()));
+            }
+        }
+
+        for validator in &self.typed_validators {
+            if let Err(e) = validator(merged, diagnostics) {
+                diagnostics.push_error(e);
+            }
+        }
+
+        diagnostics.check()
+    }
+
+    /// Move a value set under one of the field's former names to its current
+    /// path, reporting the deprecated usage.
+    fn apply_deprecated_names(
+        &self,
+        source: &dyn ConfigSource,
+        value: &mut Value,
+        mapping: &FieldMapping,
+        diagnostics: &mut Diagnostics,
+    ) {
+        let found: Vec<(Value, String, String)> = match source.source_type() {
+            Source::Environment => {
+                let Some(env) = source.as_any().downcast_ref::<Environment>() else {
+                    return;
+                };
+                mapping
+                    .deprecated
+                    .env_keys
+                    .iter()
+                    .filter_map(|key| env.lookup(key).map(|v| (v, key)))
+                    .map(|(v, key)| {
+                        (
+                            v,
+                            format!("env var {}", key),
+                            format!("env var {}", mapping.env_key),
+                        )
+                    })
+                    .collect()
+            }
+            Source::Cli => mapping
+                .deprecated
+                .cli_keys
+                .iter()
+                .filter_map(|key| source.get_value(key).map(|v| (v, key)))
+                .map(|(v, key)| {
+                    (
+                        v,
+                        format!("CLI flag --{}", key),
+                        format!("--{}", mapping.cli_key),
+                    )
+                })
+                .collect(),
+            Source::ConfigFile => mapping
+                .deprecated
+                .paths
+                .iter()
+                .filter_map(|old| path::remove(value, old).map(|v| (v, old)))
+                .map(|(v, old)| {
+                    let file = source
+                        .origin(old)
+                        .unwrap_or_else(|| Source::ConfigFile.to_string());
+                    (
+                        v,
+                        format!("key {} in {}", old, file),
+                        format!("key {}", mapping.path),
+                    )
+                })
+                .collect(),
+            Source::Default => return,
+        };
+
+        for (old_value, old_name, new_name) in found {
+            let current_set = path::get(value, &mapping.path).is_some();
+            let message = if current_set {
+                format!(
+                    "{} is deprecated and ignored because {} is set",
+                    old_name, new_name
+                )
+            } else {
+                format!("{} is deprecated, use {} instead", old_name, new_name)
+            };
+
+            if self.deny_deprecated {
+                diagnostics.error(message);
+            } else {
+                diagnostics.warn(message);
+            }
+
+            if let (false, Value::Object(map)) = (current_set, &mut *value) {
+                path::insert(map, &mapping.path, old_value);
+            }
+        }
+    }
+
+    /// Check the builder's and the schema's cross-field constraints.
+    fn check_constraints(
+        &self,
+        merged: &Value,
+        layers: &[(usize, Value, u8)],
+        diagnostics: &mut Diagnostics,
+    ) {
+        let schema_constraints = self
+            .schema
+            .as_ref()
+            .map(StructSchema::constraints)
+            .unwrap_or_default();
+
+        let origin = |path: &str| {
+            let source = self.winning_source(layers, path)?;
+            Some(ValueOrigin {
+                description: self.origin_of(layers, path)?,
+                is_default: source.source_type() == Source::Default,
+            })
+        };
+
+        for constraint in schema_constraints.iter().chain(&self.constraints) {
+            if let Err(message) = constraint.check(merged, &origin) {
+                diagnostics.error(message);
+            }
+        }
+    }
+
+    /// Describe the source whose value for `path` won the merge.
+    fn origin_of(&self, layers: &[(usize, Value, u8)], path: &str) -> Option<String> {
+        let source = self.winning_source(layers, path)?;
+        source
+            .origin(path)
+            .or_else(|| Some(source.source_type().to_string()))
+    }
+
+    fn winning_source(
+        &self,
+        layers: &[(usize, Value, u8)],
+        path: &str,
+    ) -> Option<&dyn ConfigSource> {
+        // Same order as the merge: by priority, later sources winning ties
+        let mut ordered: Vec<&(usize, Value, u8)> = layers.iter().collect();
+        ordered.sort_by_key(|(_, _, priority)| *priority);
+
+        let (index, _, _) = ordered
+            .into_iter()
+            .rev()
+            .find(|(_, value, _)| path::get(value, path).is_some_and(|v| !v.is_null()))?;
+
+        Some(self.sources[*index].as_ref())
+    }
+
     pub fn sources(&self) -> &[Box<dyn ConfigSource>] {
         &self.sources
     }
@@ -445,3 +1112,125 @@ impl ConfigBuilder {
             .find_map(|source| source.as_any().downcast_ref::<T>())
     }
 }
+
+/// Default values, the lowest-priority source.
+struct DefaultsSource {
+    value: Value,
+}
+
+impl ConfigSource for DefaultsSource {
+    fn collect(&self) -> Result<Value> {
+        Ok(self.value.clone())
+    }
+
+    fn source_type(&self) -> Source {
+        Source::Default
+    }
+
+    fn has_value(&self, key: &str) -> bool {
+        self.value.get(key).is_some()
+    }
+
+    fn get_value(&self, key: &str) -> Option<Value> {
+        self.value.get(key).cloned()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn origin(&self, _path: &str) -> Option<String> {
+        Some("default value".to_string())
+    }
+}
+
+type LoadFn = Box<dyn Fn() -> Result<Box<dyn ConfigSource>> + Send + Sync>;
+
+/// A source created on first use, see [`ConfigBuilder::add_source_deferred`].
+///
+/// A failed load is not cached, so every use reports the error.
+struct Deferred {
+    source_type: Source,
+    load: LoadFn,
+    loaded: OnceCell<Box<dyn ConfigSource>>,
+}
+
+impl Deferred {
+    fn new<F>(source_type: Source, load: F) -> Self
+    where
+        F: Fn() -> Result<Box<dyn ConfigSource>> + Send + Sync + 'static,
+    {
+        Self {
+            source_type,
+            load: Box::new(load),
+            loaded: OnceCell::new(),
+        }
+    }
+
+    fn source(&self) -> Result<&dyn ConfigSource> {
+        self.loaded
+            .get_or_try_init(|| (self.load)())
+            .map(|source| source.as_ref())
+    }
+}
+
+impl ConfigSource for Deferred {
+    fn source_type(&self) -> Source {
+        self.source_type
+    }
+
+    fn collect(&self) -> Result<Value> {
+        self.source()?.collect()
+    }
+
+    fn has_value(&self, key: &str) -> bool {
+        self.source().is_ok_and(|source| source.has_value(key))
+    }
+
+    fn get_value(&self, key: &str) -> Option<Value> {
+        self.source().ok()?.get_value(key)
+    }
+
+    // Once loaded, the source can be found by its own type
+    fn as_any(&self) -> &dyn Any {
+        match self.loaded.get() {
+            Some(source) => source.as_any(),
+            None => self,
+        }
+    }
+
+    fn origin(&self, path: &str) -> Option<String> {
+        self.loaded.get()?.origin(path)
+    }
+}
+
+/// Load a file chosen by [`ConfigFiles`]; an optional file that does not
+/// exist is empty.
+fn config_file(path: &Path, required: bool) -> Result<Config> {
+    if path.exists() {
+        Config::from_file(path)
+    } else if required {
+        Err(Error::Config(format!(
+            "Config file not found: {}",
+            path.display()
+        )))
+    } else {
+        Config::from_file_optional(path)
+    }
+}
+
+/// The warning for a value ignored because its field cannot be set from the
+/// source it came from.
+fn restriction_warning(path: &str, origin: &str, restriction: &SourceRestriction) -> String {
+    let allowed: Vec<String> = restriction.allowed.iter().map(Source::to_string).collect();
+    format!(
+        "Ignoring {} from {}: it can only be set from {}",
+        path,
+        origin,
+        if allowed.is_empty() {
+            "defaults".to_string()
+        } else {
+            allowed.join(", ")
+        }
+    )
+}
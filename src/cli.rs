@@ -1,16 +1,43 @@
 use crate::{
-    error::Result,
+    config::ConfigFormat,
+    error::{Error, Result},
+    path,
+    schema::{FieldMapping, StructSchema, ValueKind},
     source::{ConfigSource, Source},
 };
-use clap::Parser;
+use clap::{parser::ValueSource, Arg, ArgAction, ArgMatches, Command, Parser};
 use serde_json::Value;
 use std::any::Any;
 use std::collections::HashMap;
+use std::ffi::OsString;
+
+/// Separator between the parts of a CLI key addressing a nested field.
+const DEFAULT_SEPARATOR: &str = ".";
+
+const SET_FLAG: &str = "set";
+const PRINT_CONFIG_FLAG: &str = "print-config";
+const SET_JSON_FLAG: &str = "set-json";
 
 #[derive(Debug, Clone)]
 pub struct Cli {
     parsed_values: HashMap<String, Value>,
     field_mappings: HashMap<String, String>,
+    clap_defaults: serde_json::Map<String, Value>,
+    separator: String,
+    allow_set: bool,
+    /// Arguments that are not settings, e.g. `--config`; available from
+    /// `get_matches` but left out of `collect`.
+    control_keys: Vec<String>,
+    subcommand: Option<String>,
+    /// Flags of the subcommand, by field path, as `(flag, value)`.
+    subcommand_values: HashMap<String, (String, Value)>,
+    /// Arguments that are not options, in order.
+    positionals: Vec<Value>,
+    /// Fields read from positional arguments, as `(path, index, variadic)`.
+    positional_fields: Vec<(String, usize, bool)>,
+    /// Number of times each key was given as a flag without a value, for
+    /// keys never given with one.
+    flag_counts: HashMap<String, usize>,
 }
 
 impl Cli {
@@ -18,66 +45,601 @@ impl Cli {
         Self::from_vec(std::env::args().collect())
     }
 
+    /// Parse `args`, including the program name, without a clap command.
+    ///
+    /// The following GNU-style forms are understood:
+    /// - `--key value` and `--key=value`; a value may be a negative number
+    /// - `--flag`, set to `true`, and `--no-flag`, set to `false` when no
+    ///   value follows; `--no-proxy localhost` sets `no-proxy`
+    /// - `-k value`, `-k=value` and `-k8080` for single character keys
+    /// - `-vq`, setting each flag to `true`, and `-vvv`, counting to `3`
+    /// - `--`, after which every argument is positional
+    ///
+    /// A key given several times collects its values into an array, except
+    /// that a flag repeated without values counts its occurrences and the
+    /// last of several booleans, e.g. `--flag` and `--no-flag`, wins. With a
+    /// schema, a flag given once for an integer field also counts, as `1`.
+    /// Other arguments are positional and only become
+    /// settings when mapped with [`Cli::with_positional`] or
+    /// [`Cli::with_variadic`]; as an option consumes the argument after it,
+    /// put positional arguments first or after `--`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigSource};
+    /// use serde_json::json;
+    ///
+    /// let args = ["app", "--port=8080", "--offset", "-5", "--no-color", "-vvv", "--tag", "a", "--tag", "b"];
+    /// let cli = Cli::from_vec(args.iter().map(|a| a.to_string()).collect());
+    ///
+    /// assert_eq!(
+    ///     cli.collect()?,
+    ///     json!({ "port": 8080, "offset": -5, "color": false, "v": 3, "tag": ["a", "b"] })
+    /// );
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
     pub fn from_vec(args: Vec<String>) -> Self {
-        let mut parsed_values = HashMap::new();
+        // Each occurrence is `(key, value, bare)`, `bare` for a flag without a value
+        let mut occurrences: Vec<(String, Value, bool)> = Vec::new();
+        let mut positionals = Vec::new();
 
         let mut i = 1;
         while i < args.len() {
             let arg = &args[i];
+            i += 1;
 
-            if arg.starts_with("--") {
-                let key = arg.trim_start_matches("--");
+            if arg == "--" {
+                positionals.extend(args[i..].iter().map(|a| Self::parse_value(a)));
+                break;
+            }
 
-                if i + 1 < args.len() && !args[i + 1].starts_with("--") {
-                    let value = &args[i + 1];
-                    parsed_values.insert(key.to_string(), Self::parse_value(value));
-                    i += 2;
-                } else {
-                    parsed_values.insert(key.to_string(), Value::Bool(true));
+            if let Some(long) = arg.strip_prefix("--") {
+                if let Some((key, value)) = long.split_once('=') {
+                    occurrences.push((key.to_string(), Self::parse_value(value), false));
+                } else if let Some(value) = args.get(i).filter(|a| !Self::is_option(a)) {
+                    occurrences.push((long.to_string(), Self::parse_value(value), false));
                     i += 1;
+                } else if let Some(key) = long.strip_prefix("no-") {
+                    occurrences.push((key.to_string(), Value::Bool(false), false));
+                } else {
+                    occurrences.push((long.to_string(), Value::Bool(true), true));
                 }
-            } else if arg.starts_with("-") && arg.len() == 2 {
-                let key = arg.trim_start_matches("-");
+            } else if Self::is_option(arg) {
+                let short = &arg[1..];
+                let mut chars = short.chars();
+                let first = chars.next().unwrap_or_default();
+                let rest = chars.as_str();
 
-                if i + 1 < args.len() && !args[i + 1].starts_with("-") {
-                    let value = &args[i + 1];
-                    parsed_values.insert(key.to_string(), Self::parse_value(value));
-                    i += 2;
-                } else {
-                    parsed_values.insert(key.to_string(), Value::Bool(true));
+                if let Some(value) = rest.strip_prefix('=') {
+                    occurrences.push((first.to_string(), Self::parse_value(value), false));
+                } else if Self::is_number(rest) {
+                    occurrences.push((first.to_string(), Self::parse_value(rest), false));
+                } else if !rest.is_empty() {
+                    for flag in short.chars() {
+                        occurrences.push((flag.to_string(), Value::Bool(true), true));
+                    }
+                } else if let Some(value) = args.get(i).filter(|a| !Self::is_option(a)) {
+                    occurrences.push((first.to_string(), Self::parse_value(value), false));
                     i += 1;
+                } else {
+                    occurrences.push((first.to_string(), Value::Bool(true), true));
                 }
             } else {
-                i += 1;
+                positionals.push(Self::parse_value(arg));
+            }
+        }
+
+        let mut grouped: Vec<(String, Vec<(Value, bool)>)> = Vec::new();
+        for (key, value, bare) in occurrences {
+            match grouped.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, values)) => values.push((value, bare)),
+                None => grouped.push((key, vec![(value, bare)])),
             }
         }
 
+        let mut parsed_values = HashMap::new();
+        let mut flag_counts = HashMap::new();
+        for (key, values) in grouped {
+            if values.iter().all(|(_, bare)| *bare) {
+                flag_counts.insert(key.clone(), values.len());
+            }
+            parsed_values.insert(key, Self::combine_occurrences(values));
+        }
+
         Self {
             parsed_values,
             field_mappings: HashMap::new(),
+            clap_defaults: serde_json::Map::new(),
+            separator: DEFAULT_SEPARATOR.to_string(),
+            allow_set: false,
+            control_keys: Vec::new(),
+            subcommand: None,
+            subcommand_values: HashMap::new(),
+            positionals,
+            positional_fields: Vec::new(),
+            flag_counts,
+        }
+    }
+
+    /// Whether `arg` starts an option rather than being a value. `-` alone
+    /// and negative numbers are values.
+    fn is_option(arg: &str) -> bool {
+        arg.len() > 1 && arg.starts_with('-') && !Self::is_number(arg)
+    }
+
+    /// Whether `arg` is a finite number. `inf` and `nan` also parse as `f64`,
+    /// but `-inf` is a cluster of short flags.
+    fn is_number(arg: &str) -> bool {
+        arg.parse::<f64>().is_ok_and(f64::is_finite)
+    }
+
+    /// The value of a key given once or several times, from its values and
+    /// whether each was a flag without a value.
+    fn combine_occurrences(mut values: Vec<(Value, bool)>) -> Value {
+        if values.len() == 1 {
+            return values.remove(0).0;
+        }
+
+        if values.iter().all(|(_, bare)| *bare) {
+            return Value::from(values.len());
+        }
+        if values.iter().all(|(v, _)| v.is_boolean()) {
+            return values.pop().map(|(v, _)| v).unwrap_or_default();
+        }
+        Value::Array(values.into_iter().map(|(v, _)| v).collect())
+    }
+
+    /// Turn flags given once without a value into a count of `1` for the
+    /// integer fields of `mappings`, so that `-v` and `-vv` both set a
+    /// `verbose: u8`.
+    pub(crate) fn count_flags(&self, value: &mut Value, mappings: &[FieldMapping]) {
+        for (key, _) in self.flag_counts.iter().filter(|(_, count)| **count == 1) {
+            let path = self
+                .field_mappings
+                .iter()
+                .find(|(_, cli_key)| *cli_key == key)
+                .map(|(path, _)| path.clone())
+                .or_else(|| self.nested_path(key))
+                .unwrap_or_else(|| key.clone());
+
+            let integer = mappings
+                .iter()
+                .any(|m| m.path == path && m.kind == ValueKind::Integer);
+            if !integer || path::get(value, &path) != Some(&Value::Bool(true)) {
+                continue;
+            }
+            if let Value::Object(map) = value {
+                path::insert(map, &path, Value::from(1));
+            }
+        }
+    }
+
+    /// Build a `clap::Command` with a flag for every leaf field of `schema`.
+    ///
+    /// Each flag gets its short name from `#[gonfig(short = '...')]`, its help
+    /// text from the field's doc comment and its value name from the field's
+    /// type. The environment variable and default value are shown in the help
+    /// as `[env: APP_PORT] [default: 8080]`; they are applied by the other
+    /// sources, not by clap. Fields whose `sources` exclude the CLI get no
+    /// flag, and former names from `#[gonfig(alias = "...")]` are accepted as
+    /// hidden flags.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, Gonfig, GonfigSchema};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Gonfig)]
+    /// #[Gonfig(env_prefix = "APP")]
+    /// struct Config {
+    ///     /// Port to listen on
+    ///     #[gonfig(short = 'p', default = "8080")]
+    ///     port: u16,
+    /// }
+    ///
+    /// let help = Cli::command(&Config::gonfig_schema()).render_help().to_string();
+    /// assert!(help.contains("-p, --port <INT>"));
+    /// assert!(help.contains("Port to listen on [env: APP_PORT] [default: 8080]"));
+    /// ```
+    pub fn command(schema: &StructSchema) -> Command {
+        let mut command = Command::new(schema.name.to_lowercase());
+        if let Some(about) = schema.about {
+            command = command.about(first_paragraph(about)).long_about(about);
+        }
+
+        let mappings = schema.field_mappings();
+        command = Self::with_args(command, mappings.iter().filter(|m| m.subcommand.is_none()));
+
+        // Shared flags are also accepted after the subcommand, unless it has
+        // a flag of the same name
+        let subcommand_flags: Vec<&str> = mappings
+            .iter()
+            .filter(|m| m.subcommand.is_some())
+            .map(|m| m.cli_key.as_str())
+            .collect();
+        if !subcommand_flags.is_empty() {
+            command = command.mut_args(|arg| {
+                let shared = !subcommand_flags.contains(&arg.get_id().as_str());
+                arg.global(shared)
+            });
+        }
+
+        for (_, commands) in schema.subcommands() {
+            for variant in &commands.variants {
+                let mut subcommand = Command::new(variant.name);
+                if let Some(about) = variant.about {
+                    subcommand = subcommand.about(first_paragraph(about)).long_about(about);
+                }
+                let variant_mappings = mappings
+                    .iter()
+                    .filter(|m| m.subcommand == Some(variant.name));
+                command = command.subcommand(Self::with_args(subcommand, variant_mappings));
+            }
+        }
+        command
+    }
+
+    fn with_args<'a>(
+        mut command: Command,
+        mappings: impl Iterator<Item = &'a FieldMapping>,
+    ) -> Command {
+        let mut seen: Vec<&str> = Vec::new();
+        for mapping in mappings.filter(|m| m.allows(Source::Cli)) {
+            // Overridden names may collide; the first field keeps the flag
+            if seen.contains(&mapping.cli_key.as_str()) {
+                continue;
+            }
+            command = command.arg(Self::arg(mapping));
+            seen.push(&mapping.cli_key);
+
+            // Former names are separate hidden flags, so that the builder can
+            // tell them apart and report their use
+            if mapping.positional.is_some() {
+                continue;
+            }
+            for key in &mapping.deprecated.cli_keys {
+                if seen.contains(&key.as_str()) {
+                    continue;
+                }
+                let arg = Arg::new(key.clone()).long(key.clone()).hide(true);
+                command = command.arg(Self::value_parser(arg, mapping));
+                seen.push(key);
+            }
+        }
+        command
+    }
+
+    fn arg(mapping: &FieldMapping) -> Arg {
+        let mut arg = match mapping.positional {
+            Some(index) => Arg::new(mapping.cli_key.clone())
+                .index(index + 1)
+                .value_name(mapping.cli_key.to_uppercase().replace('-', "_")),
+            None => Arg::new(mapping.cli_key.clone())
+                .long(mapping.cli_key.clone())
+                .value_name(mapping.kind.value_name()),
+        };
+
+        if let Some(short) = mapping.short {
+            arg = arg.short(short);
+        }
+
+        let mut tags = String::new();
+        if !mapping.env_key.is_empty() && mapping.allows(Source::Environment) {
+            tags.push_str(&format!(" [env: {}]", mapping.env_key));
+        }
+        if let Some(default) = &mapping.default_value {
+            let default = match default {
+                Value::String(s) => s.clone(),
+                default => default.to_string(),
+            };
+            tags.push_str(&format!(" [default: {}]", default));
+        }
+
+        let help = mapping.doc.map(first_paragraph).unwrap_or_default() + &tags;
+        arg = arg.help(help.trim_start().to_string());
+        if let Some(doc) = mapping.doc.filter(|d| d.contains("\n\n")) {
+            arg = arg.long_help(format!("{}{}", doc, tags));
+        }
+
+        Self::value_parser(arg, mapping)
+    }
+
+    /// Parse the values of `arg` according to the kind of the field.
+    fn value_parser(arg: Arg, mapping: &FieldMapping) -> Arg {
+        match mapping.kind {
+            ValueKind::Bool => arg
+                .num_args(0..=1)
+                .require_equals(false)
+                .default_missing_value("true")
+                .value_parser(clap::value_parser!(bool))
+                .hide_possible_values(true),
+            ValueKind::Integer => arg.value_parser(clap::value_parser!(i64)),
+            ValueKind::Float => arg.value_parser(clap::value_parser!(f64)),
+            ValueKind::List if mapping.positional.is_some() => {
+                arg.num_args(1..).action(ArgAction::Append)
+            }
+            ValueKind::List => arg.action(ArgAction::Append),
+            ValueKind::String | ValueKind::Path | ValueKind::Any => arg,
+        }
+    }
+
+    /// Parse `std::env::args_os()` with `command` built by [`Cli::command`].
+    ///
+    /// `--help` and `--version` print their output and exit the process, as
+    /// with clap. Any other parse error, such as an unknown flag or a value of
+    /// the wrong type, is returned as [`Error::Cli`].
+    pub fn from_command(command: Command, schema: &StructSchema) -> Result<Self> {
+        Self::from_command_args(command, schema, std::env::args_os())
+    }
+
+    /// Parse `args`, including the program name, with `command` built by
+    /// [`Cli::command`].
+    ///
+    /// Behaves like [`Cli::from_command`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigSource, Gonfig, GonfigSchema};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Gonfig)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// let schema = Config::gonfig_schema();
+    /// let cli = Cli::from_command_args(Cli::command(&schema), &schema, ["app", "--port", "80"])?;
+    /// assert_eq!(cli.collect()?["port"], 80);
+    ///
+    /// let unknown = Cli::from_command_args(Cli::command(&schema), &schema, ["app", "--prot", "80"]);
+    /// assert!(unknown.is_err());
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn from_command_args<I, T>(command: Command, schema: &StructSchema, args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = command
+            .try_get_matches_from(args)
+            .map_err(|e| match e.kind() {
+                clap::error::ErrorKind::DisplayHelp
+                | clap::error::ErrorKind::DisplayVersion
+                | clap::error::ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => e.exit(),
+                _ => Error::Cli(e.to_string()),
+            })?;
+        Ok(Self::from_matches(&matches, schema))
+    }
+
+    /// Take the values of the fields of `schema` from `matches`.
+    ///
+    /// Only arguments passed on the command line are used, so clap defaults
+    /// never override files or environment variables. Arguments that are not
+    /// fields of the schema, e.g. a `--config` flag, are kept by their name in
+    /// [`Cli::get_matches`] but are not configuration values.
+    pub fn from_matches(matches: &ArgMatches, schema: &StructSchema) -> Self {
+        let mut cli = Self {
+            parsed_values: HashMap::new(),
+            field_mappings: HashMap::new(),
+            clap_defaults: serde_json::Map::new(),
+            separator: DEFAULT_SEPARATOR.to_string(),
+            allow_set: false,
+            control_keys: Vec::new(),
+            subcommand: None,
+            subcommand_values: HashMap::new(),
+            positionals: Vec::new(),
+            positional_fields: Vec::new(),
+            flag_counts: HashMap::new(),
+        };
+
+        let mappings = schema.field_mappings();
+        for id in matches.ids() {
+            let id = id.as_str();
+            if matches.value_source(id) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+
+            let mapping = mappings.iter().find(|m| {
+                m.subcommand.is_none()
+                    && (m.cli_key == id || m.deprecated.cli_keys.iter().any(|k| k == id))
+            });
+            if mapping.is_some_and(|m| m.positional.is_some() && m.cli_key == id) {
+                continue;
+            }
+            if mapping.is_none() && id != SET_FLAG && id != SET_JSON_FLAG {
+                cli.control_keys.push(id.to_string());
+            }
+            let kind = mapping.map(|m| m.kind).unwrap_or(ValueKind::Any);
+            if let Some(value) = Self::match_value(matches, id, kind) {
+                cli.parsed_values.insert(id.to_string(), value);
+            }
+        }
+
+        if let Some((name, sub_matches)) = matches.subcommand() {
+            cli.subcommand = Some(name.to_string());
+            for id in sub_matches.ids() {
+                let id = id.as_str();
+                if sub_matches.value_source(id) != Some(ValueSource::CommandLine) {
+                    continue;
+                }
+                let Some(mapping) = mappings
+                    .iter()
+                    .find(|m| m.subcommand == Some(name) && m.cli_key == id)
+                else {
+                    continue;
+                };
+                if let Some(value) = Self::match_value(sub_matches, id, mapping.kind) {
+                    cli.subcommand_values
+                        .insert(mapping.path.clone(), (id.to_string(), value));
+                }
+            }
+        }
+
+        // Positional values are laid out again by index, so that they are
+        // read like those of `from_vec`
+        let mut positional: Vec<&FieldMapping> = mappings
+            .iter()
+            .filter(|m| m.subcommand.is_none() && m.positional.is_some() && m.allows(Source::Cli))
+            .collect();
+        positional.sort_by_key(|m| m.positional);
+        for mapping in positional {
+            let index = mapping.positional.unwrap_or_default();
+            let variadic = mapping.kind == ValueKind::List;
+            cli.positional_fields
+                .push((mapping.path.clone(), index, variadic));
+
+            if matches.value_source(&mapping.cli_key) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+            if let Some(value) = Self::match_value(matches, &mapping.cli_key, mapping.kind) {
+                cli.positionals.resize(index, Value::Null);
+                match value {
+                    Value::Array(values) if variadic => cli.positionals.extend(values),
+                    value => cli.positionals.push(value),
+                }
+            }
+        }
+
+        for mapping in mappings
+            .into_iter()
+            .filter(|m| m.subcommand.is_none() && m.positional.is_none())
+        {
+            cli.field_mappings.insert(mapping.path, mapping.cli_key);
+        }
+        cli
+    }
+
+    /// Name of the subcommand given on the command line, if any.
+    ///
+    /// Set by [`Cli::from_matches`] for commands built from a schema with a
+    /// `#[gonfig(subcommand)]` field; the builder uses it to select the
+    /// variant, see [`StructSchema::assemble_subcommands`].
+    pub fn subcommand(&self) -> Option<&str> {
+        self.subcommand.as_deref()
+    }
+
+    fn match_value(matches: &ArgMatches, id: &str, kind: ValueKind) -> Option<Value> {
+        match kind {
+            ValueKind::Bool => matches.get_one::<bool>(id).map(|b| Value::Bool(*b)),
+            ValueKind::Integer => matches.get_one::<i64>(id).map(|n| Value::from(*n)),
+            ValueKind::Float => matches
+                .get_one::<f64>(id)
+                .and_then(|n| serde_json::Number::from_f64(*n))
+                .map(Value::Number),
+            ValueKind::String | ValueKind::Path => {
+                matches.get_one::<String>(id).cloned().map(Value::String)
+            }
+            ValueKind::List | ValueKind::Any => {
+                let values: Vec<Value> = matches
+                    .try_get_many::<String>(id)
+                    .ok()??
+                    .map(|v| Self::parse_value(v))
+                    .collect();
+                match (kind, values.len()) {
+                    (ValueKind::Any, 1) => values.into_iter().next(),
+                    _ => Some(Value::Array(values)),
+                }
+            }
         }
     }
 
+    /// Parse `std::env::args_os()` with the clap parser `T`.
+    ///
+    /// Only arguments passed on the command line, or through a clap `env`
+    /// attribute, are taken as CLI values. Values of clap `default_value`s are
+    /// kept apart in [`Cli::clap_defaults`] and options that were not passed
+    /// are left out, so they never override files or environment variables.
+    /// Errors, `--help` and `--version` exit the process, as with
+    /// `T::parse()`.
     pub fn with_clap_app<T: Parser + serde::Serialize>() -> Result<Self> {
-        let app = T::parse();
+        Self::from_clap_matches::<T>(&T::command().get_matches())
+    }
+
+    /// Parse `args`, including the program name, with the clap parser `T`.
+    ///
+    /// Behaves like [`Cli::with_clap_app`], except that parse errors are
+    /// returned as [`Error::Cli`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap::Parser;
+    /// use gonfig::{Cli, ConfigSource};
+    /// use serde::Serialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Parser, Serialize)]
+    /// struct Args {
+    ///     #[arg(long, default_value = "8080")]
+    ///     port: u16,
+    ///     #[arg(long)]
+    ///     host: Option<String>,
+    ///     #[arg(long)]
+    ///     verbose: bool,
+    /// }
+    ///
+    /// let cli = Cli::with_clap_app_from::<Args, _, _>(["app", "--verbose"])?;
+    /// assert_eq!(cli.collect()?, json!({ "verbose": true }));
+    /// assert_eq!(cli.clap_defaults(), json!({ "port": 8080 }));
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn with_clap_app_from<T, I, S>(args: I) -> Result<Self>
+    where
+        T: Parser + serde::Serialize,
+        I: IntoIterator<Item = S>,
+        S: Into<OsString> + Clone,
+    {
+        let matches = T::command()
+            .try_get_matches_from(args)
+            .map_err(|e| Error::Cli(e.to_string()))?;
+        Self::from_clap_matches::<T>(&matches)
+    }
+
+    fn from_clap_matches<T: Parser + serde::Serialize>(matches: &ArgMatches) -> Result<Self> {
+        let app = T::from_arg_matches(matches).map_err(|e| Error::Cli(e.to_string()))?;
 
         let json_value = serde_json::to_value(&app).map_err(|e| {
             crate::error::Error::Serialization(format!("Failed to serialize clap args: {}", e))
         })?;
 
-        let mut parsed_values = HashMap::new();
+        let ids: Vec<String> = T::command()
+            .get_arguments()
+            .map(|arg| arg.get_id().to_string())
+            .collect();
+
+        let mut explicit = serde_json::Map::new();
+        let mut clap_defaults = serde_json::Map::new();
         if let Value::Object(map) = json_value {
-            for (key, value) in map {
-                parsed_values.insert(key, value);
-            }
+            split_by_value_source(map, matches, &ids, &mut explicit, &mut clap_defaults);
         }
 
         Ok(Self {
-            parsed_values,
+            parsed_values: explicit.into_iter().collect(),
             field_mappings: HashMap::new(),
+            clap_defaults,
+            separator: DEFAULT_SEPARATOR.to_string(),
+            allow_set: false,
+            control_keys: Vec::new(),
+            subcommand: None,
+            subcommand_values: HashMap::new(),
+            positionals: Vec::new(),
+            positional_fields: Vec::new(),
+            flag_counts: HashMap::new(),
         })
     }
 
+    /// Values of clap `default_value`s that were not overridden on the
+    /// command line, as an object.
+    ///
+    /// [`ConfigBuilder::with_cli_custom`](crate::ConfigBuilder::with_cli_custom)
+    /// adds them as defaults, below every other source.
+    pub fn clap_defaults(&self) -> Value {
+        Value::Object(self.clap_defaults.clone())
+    }
+
     pub fn with_field_mapping(
         mut self,
         field_name: impl Into<String>,
@@ -88,6 +650,311 @@ impl Cli {
         self
     }
 
+    /// Read the field at `field_path` from the positional argument at
+    /// `index`, counting from 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigSource};
+    ///
+    /// let args = ["app", "deploy", "web1", "web2", "--dry-run"];
+    /// let cli = Cli::from_vec(args.iter().map(|a| a.to_string()).collect())
+    ///     .with_positional("action", 0)
+    ///     .with_variadic("hosts", 1);
+    ///
+    /// let config = cli.collect()?;
+    /// assert_eq!(config["action"], "deploy");
+    /// assert_eq!(config["hosts"], serde_json::json!(["web1", "web2"]));
+    /// assert_eq!(config["dry-run"], true);
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn with_positional(mut self, field_path: impl Into<String>, index: usize) -> Self {
+        self.positional_fields
+            .push((field_path.into(), index, false));
+        self
+    }
+
+    /// Read the field at `field_path` as a list of the positional arguments
+    /// from `index` on, e.g. input files.
+    pub fn with_variadic(mut self, field_path: impl Into<String>, index: usize) -> Self {
+        self.positional_fields
+            .push((field_path.into(), index, true));
+        self
+    }
+
+    /// Read the fields of `schema` from the arguments parsed by
+    /// [`Cli::from_vec`], by their flags, short flags and positions.
+    ///
+    /// Unlike [`Cli::from_command_args`] nothing is rejected: arguments that
+    /// are not fields are kept as with `from_vec`. A first positional argument
+    /// naming a `#[gonfig(subcommand)]` variant selects it, and the flags of
+    /// its fields are read as that subcommand's.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigSource, Gonfig, GonfigSchema};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Gonfig)]
+    /// struct Config {
+    ///     #[gonfig(short = 'p')]
+    ///     port: u16,
+    /// }
+    ///
+    /// let args = ["app", "-p", "80", "--nocapture"];
+    /// let cli = Cli::from_vec(args.iter().map(|a| a.to_string()).collect())
+    ///     .with_schema(&Config::gonfig_schema());
+    ///
+    /// assert_eq!(cli.collect()?["port"], 80);
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn with_schema(mut self, schema: &StructSchema) -> Self {
+        let mappings = schema.field_mappings();
+
+        let subcommand = self
+            .positionals
+            .first()
+            .and_then(Value::as_str)
+            .and_then(|name| {
+                schema
+                    .subcommands()
+                    .flat_map(|(_, commands)| &commands.variants)
+                    .map(|variant| variant.name)
+                    .find(|variant| *variant == name)
+            });
+        if let Some(name) = subcommand {
+            self.positionals.remove(0);
+            for mapping in mappings.iter().filter(|m| m.subcommand == Some(name)) {
+                if let Some(value) = self.parsed_values.remove(&mapping.cli_key) {
+                    self.subcommand_values
+                        .insert(mapping.path.clone(), (mapping.cli_key.clone(), value));
+                }
+            }
+            self.subcommand = Some(name.to_string());
+        }
+
+        for mapping in mappings.iter().filter(|m| m.subcommand.is_none()) {
+            if let Some(index) = mapping.positional {
+                if mapping.allows(Source::Cli) {
+                    let variadic = mapping.kind == ValueKind::List;
+                    self.positional_fields
+                        .push((mapping.path.clone(), index, variadic));
+                }
+                continue;
+            }
+
+            // A short flag is read as its long one when that was not given
+            let short = mapping.short.map(String::from);
+            if let Some(short) =
+                short.filter(|_| !self.parsed_values.contains_key(&mapping.cli_key))
+            {
+                if let Some(value) = self.parsed_values.remove(&short) {
+                    self.parsed_values.insert(mapping.cli_key.clone(), value);
+                }
+                if let Some(count) = self.flag_counts.remove(&short) {
+                    self.flag_counts.insert(mapping.cli_key.clone(), count);
+                }
+            }
+            // A list flag given once holds a list, as with clap
+            if mapping.kind == ValueKind::List {
+                if let Some(value) = self.parsed_values.get_mut(&mapping.cli_key) {
+                    if !value.is_array() {
+                        *value = Value::Array(vec![value.take()]);
+                    }
+                }
+            }
+            self.field_mappings
+                .insert(mapping.path.clone(), mapping.cli_key.clone());
+        }
+        self
+    }
+
+    /// The value of the positional field at `index`, if any was given.
+    fn positional_value(&self, index: usize, variadic: bool) -> Option<Value> {
+        if !variadic {
+            return self
+                .positionals
+                .get(index)
+                .filter(|v| !v.is_null())
+                .cloned();
+        }
+        let rest = self.positionals.get(index..).unwrap_or_default();
+        (!rest.is_empty()).then(|| Value::Array(rest.to_vec()))
+    }
+
+    /// Set the separator between the parts of a key addressing a nested field.
+    ///
+    /// The default separator is `"."`, so `--database.host=db1` sets `host`
+    /// inside `database`. Parts are written in kebab-case and converted to
+    /// snake_case, e.g. `--database.pool-size` sets `database.pool_size`. Keys
+    /// registered with [`with_field_mapping`](Self::with_field_mapping) are
+    /// never split.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigSource};
+    /// use serde_json::json;
+    ///
+    /// let args = vec!["app".into(), "--database-host".into(), "db1".into()];
+    /// let cli = Cli::from_vec(args).separator("-");
+    ///
+    /// assert_eq!(cli.collect()?, json!({ "database": { "host": "db1" } }));
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn separator(mut self, sep: impl Into<String>) -> Self {
+        self.separator = sep.into();
+        self
+    }
+
+    /// Enable the `--set PATH=VALUE` and `--set-json PATH=JSON` flags.
+    ///
+    /// Both can be repeated and override any setting without a dedicated
+    /// flag, including array elements such as `servers[0].port=81`. `--set`
+    /// values are parsed like other flag values; `--set-json` values must be
+    /// valid JSON. Overrides are applied after the other flags, `--set-json`
+    /// after `--set`. Add [`Cli::set_args`] to a clap command to accept them
+    /// there.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigSource};
+    /// use serde_json::json;
+    ///
+    /// let args = ["app", "--set", "log.level=debug", "--set-json", "log.targets=[\"stderr\"]"];
+    /// let cli = Cli::from_vec(args.iter().map(|a| a.to_string()).collect()).allow_set(true);
+    ///
+    /// assert_eq!(cli.collect()?, json!({ "log": { "level": "debug", "targets": ["stderr"] } }));
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn allow_set(mut self, allow: bool) -> Self {
+        self.allow_set = allow;
+        self
+    }
+
+    /// The clap arguments for `--set` and `--set-json`, see [`Cli::allow_set`].
+    pub fn set_args() -> [Arg; 2] {
+        [
+            Arg::new(SET_FLAG)
+                .long(SET_FLAG)
+                .value_name("PATH=VALUE")
+                .action(ArgAction::Append)
+                .help("Override any setting, e.g. servers[0].port=81; can be repeated"),
+            Arg::new(SET_JSON_FLAG)
+                .long(SET_JSON_FLAG)
+                .value_name("PATH=JSON")
+                .action(ArgAction::Append)
+                .help("Override any setting with a JSON value; can be repeated"),
+        ]
+    }
+
+    /// The clap argument for `--print-config [json|yaml|toml]`, see
+    /// [`Cli::print_config_format`].
+    pub fn print_config_arg() -> Arg {
+        Arg::new(PRINT_CONFIG_FLAG)
+            .long(PRINT_CONFIG_FLAG)
+            .value_name("FORMAT")
+            .num_args(0..=1)
+            .default_missing_value("json")
+            .value_parser(["json", "yaml", "toml"])
+            .help("Print the effective configuration, with secrets redacted, and exit")
+    }
+
+    /// The format requested with `--print-config`, if it was passed.
+    ///
+    /// `--print-config` alone selects JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{Cli, ConfigFormat};
+    ///
+    /// let cli = Cli::from_vec(vec!["app".into(), "--print-config".into(), "yaml".into()]);
+    /// assert_eq!(cli.print_config_format()?, Some(ConfigFormat::Yaml));
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Cli`] for a format other than `json`, `yaml` or `toml`.
+    pub fn print_config_format(&self) -> Result<Option<ConfigFormat>> {
+        match self.parsed_values.get(PRINT_CONFIG_FLAG) {
+            None => Ok(None),
+            Some(Value::Bool(true)) => Ok(Some(ConfigFormat::Json)),
+            Some(Value::String(format)) => ConfigFormat::from_extension(format)
+                .filter(|_| format != "yml")
+                .map(Some)
+                .ok_or_else(|| {
+                    Error::Cli(format!(
+                        "Invalid --{} format '{}': expected json, yaml or toml",
+                        PRINT_CONFIG_FLAG, format
+                    ))
+                }),
+            Some(other) => Err(Error::Cli(format!(
+                "Invalid --{} format {}: expected json, yaml or toml",
+                PRINT_CONFIG_FLAG, other
+            ))),
+        }
+    }
+
+    /// The `--set` and `--set-json` overrides as `(path, value)`, in the
+    /// order they are applied.
+    pub(crate) fn set_overrides(&self) -> Result<Vec<(String, Value)>> {
+        if !self.allow_set {
+            return Ok(Vec::new());
+        }
+
+        let mut overrides = Vec::new();
+        for flag in [SET_FLAG, SET_JSON_FLAG] {
+            let raw = match self.parsed_values.get(flag) {
+                Some(Value::Array(values)) => values.clone(),
+                Some(value) => vec![value.clone()],
+                None => continue,
+            };
+
+            for raw in raw {
+                let Some((path, value)) = raw.as_str().and_then(|r| r.split_once('=')) else {
+                    return Err(Error::Cli(format!(
+                        "--{} expects PATH=VALUE, got {}",
+                        flag, raw
+                    )));
+                };
+                let value = if flag == SET_JSON_FLAG {
+                    serde_json::from_str(value).map_err(|e| {
+                        Error::Cli(format!("Invalid JSON for --{} {}: {}", flag, path, e))
+                    })?
+                } else {
+                    Self::parse_value(value)
+                };
+                overrides.push((path.trim().to_string(), value));
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// The dotted field path addressed by an unmapped `cli_key` containing
+    /// the separator.
+    fn nested_path(&self, cli_key: &str) -> Option<String> {
+        if self.separator.is_empty()
+            || !cli_key.contains(self.separator.as_str())
+            || self.field_mappings.values().any(|key| key == cli_key)
+        {
+            return None;
+        }
+
+        let parts: Vec<String> = cli_key
+            .split(self.separator.as_str())
+            .map(|part| part.replace('-', "_"))
+            .collect();
+        if parts.iter().any(String::is_empty) {
+            return None;
+        }
+        Some(parts.join("."))
+    }
+
     fn parse_value(value: &str) -> Value {
         if let Ok(b) = value.parse::<bool>() {
             return Value::Bool(b);
@@ -124,12 +991,48 @@ impl ConfigSource for Cli {
     }
 
     fn collect(&self) -> Result<Value> {
-        Ok(Value::Object(
-            self.parsed_values
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-        ))
+        let mut result = serde_json::Map::new();
+        for (key, value) in &self.parsed_values {
+            if self.allow_set && (key == SET_FLAG || key == SET_JSON_FLAG)
+                || self.control_keys.contains(key)
+            {
+                continue;
+            }
+            match self.nested_path(key) {
+                Some(path) => path::insert(&mut result, &path, value.clone()),
+                None => {
+                    result.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        // Mapped flags are also placed at their field path, which may be nested
+        for (field_name, cli_key) in &self.field_mappings {
+            if let Some(value) = self.parsed_values.get(cli_key) {
+                path::insert(&mut result, field_name, value.clone());
+            }
+        }
+
+        for (field_path, (_, value)) in &self.subcommand_values {
+            path::insert(&mut result, field_path, value.clone());
+        }
+
+        for (field_path, index, variadic) in &self.positional_fields {
+            if let Some(value) = self.positional_value(*index, *variadic) {
+                path::insert(&mut result, field_path, value);
+            }
+        }
+
+        // Overrides indexing into arrays need the merged value, so the
+        // builder applies them after merging
+        let mut result = Value::Object(result);
+        for (path, value) in self.set_overrides()? {
+            if !path::has_index(&path) {
+                path::set_indexed(&mut result, &path, value).map_err(Error::Cli)?;
+            }
+        }
+
+        Ok(result)
     }
 
     fn has_value(&self, key: &str) -> bool {
@@ -143,4 +1046,95 @@ impl ConfigSource for Cli {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn origin(&self, path: &str) -> Option<String> {
+        if let (Some(subcommand), Some((flag, _))) =
+            (&self.subcommand, self.subcommand_values.get(path))
+        {
+            return Some(format!("CLI flag {} --{}", subcommand, flag));
+        }
+
+        let set = self.set_overrides().unwrap_or_default();
+        if let Some((set_path, _)) = set.iter().rev().find(|(p, _)| p == path) {
+            return Some(format!("CLI flag --set {}", set_path));
+        }
+
+        if let Some((_, index, _)) = self.positional_fields.iter().find(|(p, _, _)| p == path) {
+            return Some(format!("CLI argument #{}", index + 1));
+        }
+
+        let cli_key = self
+            .field_mappings
+            .get(path)
+            .filter(|key| self.parsed_values.contains_key(*key))
+            .or_else(|| {
+                self.parsed_values
+                    .keys()
+                    .find(|key| self.nested_path(key).as_deref() == Some(path))
+            })
+            .map(String::as_str)
+            .unwrap_or(path);
+        Some(format!("CLI flag --{}", cli_key))
+    }
+}
+
+/// Sort the serialized clap struct into values the user passed and values of
+/// clap defaults, using the value source of the argument each key belongs to.
+///
+/// Objects that are not arguments, e.g. `#[command(flatten)]` structs, are
+/// sorted key by key. Other keys that do not name an argument cannot be traced
+/// back to one and are kept when not `null`.
+fn split_by_value_source(
+    map: serde_json::Map<String, Value>,
+    matches: &ArgMatches,
+    ids: &[String],
+    explicit: &mut serde_json::Map<String, Value>,
+    defaults: &mut serde_json::Map<String, Value>,
+) {
+    for (key, value) in map {
+        if value.is_null() {
+            continue;
+        }
+
+        if ids.contains(&key) {
+            match matches.value_source(&key) {
+                Some(ValueSource::DefaultValue) => {
+                    defaults.insert(key, value);
+                }
+                Some(_) => {
+                    explicit.insert(key, value);
+                }
+                None => {}
+            }
+        } else if let Value::Object(nested) = value {
+            let mut nested_explicit = serde_json::Map::new();
+            let mut nested_defaults = serde_json::Map::new();
+            split_by_value_source(
+                nested,
+                matches,
+                ids,
+                &mut nested_explicit,
+                &mut nested_defaults,
+            );
+            if !nested_explicit.is_empty() {
+                explicit.insert(key.clone(), Value::Object(nested_explicit));
+            }
+            if !nested_defaults.is_empty() {
+                defaults.insert(key, Value::Object(nested_defaults));
+            }
+        } else {
+            explicit.insert(key, value);
+        }
+    }
+}
+
+/// The first paragraph of a doc comment, on one line.
+fn first_paragraph(doc: &str) -> String {
+    doc.split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
@@ -105,6 +105,56 @@ impl ConfigFormat {
             }
         }
     }
+
+    /// Serialize a configuration value in the format.
+    ///
+    /// `null` values are left out of TOML, which cannot represent them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Serialization`] if the value cannot be represented in
+    /// the format, e.g. a TOML document whose root is not a table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::ConfigFormat;
+    /// use serde_json::json;
+    ///
+    /// let toml = ConfigFormat::Toml.serialize(&json!({ "port": 8080, "host": null }))?;
+    /// assert_eq!(toml, "port = 8080\n");
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn serialize(&self, value: &Value) -> Result<String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value)
+                .map_err(|e| Error::Serialization(format!("JSON serialization error: {}", e))),
+            ConfigFormat::Yaml => serde_yaml::to_string(value)
+                .map_err(|e| Error::Serialization(format!("YAML serialization error: {}", e))),
+            ConfigFormat::Toml => toml::to_string_pretty(&without_nulls(value))
+                .map_err(|e| Error::Serialization(format!("TOML serialization error: {}", e))),
+        }
+    }
+}
+
+/// `value` with every `null` object entry and array element removed.
+pub(crate) fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), without_nulls(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        other => other.clone(),
+    }
 }
 
 /// Configuration file source.
@@ -355,4 +405,150 @@ impl ConfigSource for Config {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn origin(&self, _path: &str) -> Option<String> {
+        Some(format!("config file {}", self.path.display()))
+    }
+}
+
+/// Locations of the configuration files to load.
+///
+/// The files are taken from the first of these that is set:
+///
+/// 1. the CLI flag, e.g. `--config app.toml`, repeatable
+/// 2. the environment variable, e.g. `APP_CONFIG=base.toml:local.toml`,
+///    separated like `PATH`
+/// 3. the listed default paths
+///
+/// Files are loaded in order and later files override earlier ones. A file
+/// that cannot be parsed is always an error. A missing file is an error too,
+/// unless it is one of the default paths and the files are [`optional`].
+///
+/// This is what `#[Gonfig(config_file = "...", config_file_env = "...",
+/// config_file_cli = "...", config_optional)]` configures.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use gonfig::{ConfigBuilder, ConfigFiles};
+///
+/// let files = ConfigFiles::new()
+///     .path("/etc/myapp/config.toml")
+///     .path("myapp.toml")
+///     .env_var("MYAPP_CONFIG")
+///     .cli_flag("config")
+///     .optional(true);
+///
+/// let builder = ConfigBuilder::new().with_config_files(&files)?;
+/// # Ok::<(), gonfig::Error>(())
+/// ```
+///
+/// [`optional`]: ConfigFiles::optional
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFiles {
+    paths: Vec<PathBuf>,
+    env_var: Option<String>,
+    cli_flag: Option<String>,
+    optional: bool,
+}
+
+impl ConfigFiles {
+    /// Create an empty set of locations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a default path.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Read the paths from an environment variable when it is set.
+    pub fn env_var(mut self, name: impl Into<String>) -> Self {
+        self.env_var = Some(name.into());
+        self
+    }
+
+    /// Read the paths from a CLI flag (without leading dashes) when it is passed.
+    pub fn cli_flag(mut self, flag: impl Into<String>) -> Self {
+        self.cli_flag = Some(flag.into());
+        self
+    }
+
+    /// Skip default paths that do not exist instead of failing.
+    pub fn optional(mut self, optional: bool) -> Self {
+        self.optional = optional;
+        self
+    }
+
+    /// The clap argument for the CLI flag, if one is set.
+    ///
+    /// Add it to a command built by [`Cli::command`](crate::Cli::command) so
+    /// that the flag is accepted.
+    pub fn cli_arg(&self) -> Option<clap::Arg> {
+        let flag = self.cli_flag.as_ref()?;
+        let mut help = "Configuration file to load; can be repeated".to_string();
+        if let Some(env_var) = &self.env_var {
+            help = format!("{} [env: {}]", help, env_var);
+        }
+        Some(
+            clap::Arg::new(flag.clone())
+                .long(flag.clone())
+                .value_name("PATH")
+                .action(clap::ArgAction::Append)
+                .help(help),
+        )
+    }
+
+    /// The default paths, in order.
+    pub(crate) fn default_paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Name of the environment variable overriding the paths, if any.
+    pub(crate) fn env_var_name(&self) -> Option<&str> {
+        self.env_var.as_deref()
+    }
+
+    /// Name of the CLI flag overriding the paths, if any.
+    pub(crate) fn cli_flag_name(&self) -> Option<&str> {
+        self.cli_flag.as_deref()
+    }
+
+    /// Resolve the files to load as `(path, required)`, reading the CLI flag
+    /// from `cli`.
+    pub(crate) fn resolve(&self, cli: &crate::Cli) -> Vec<(PathBuf, bool)> {
+        let from_cli = self
+            .cli_flag
+            .as_ref()
+            .and_then(|flag| cli.get_matches().get(flag));
+        if let Some(value) = from_cli {
+            let values = match value {
+                Value::Array(items) => items.iter().collect(),
+                value => vec![value],
+            };
+            return values
+                .into_iter()
+                .map(|v| match v {
+                    Value::String(s) => (PathBuf::from(s), true),
+                    other => (PathBuf::from(other.to_string()), true),
+                })
+                .collect();
+        }
+
+        let from_env = self
+            .env_var
+            .as_ref()
+            .and_then(std::env::var_os)
+            .filter(|v| !v.is_empty());
+        if let Some(value) = from_env {
+            return std::env::split_paths(&value).map(|p| (p, true)).collect();
+        }
+
+        self.paths
+            .iter()
+            .map(|p| (p.clone(), !self.optional))
+            .collect()
+    }
 }
@@ -1,11 +1,12 @@
 use crate::{
     error::Result,
+    path,
     source::{ConfigSource, Source},
     Prefix,
 };
 use serde_json::{json, Map, Value};
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 
 /// Environment variable configuration source.
@@ -56,6 +57,8 @@ pub struct Environment {
     case_sensitive: bool,
     overrides: HashMap<String, String>,
     field_mappings: HashMap<String, String>,
+    field_aliases: HashMap<String, Vec<String>>,
+    raw_fields: HashSet<String>,
 }
 
 impl Default for Environment {
@@ -66,6 +69,8 @@ impl Default for Environment {
             case_sensitive: false,
             overrides: HashMap::new(),
             field_mappings: HashMap::new(),
+            field_aliases: HashMap::new(),
+            raw_fields: HashSet::new(),
         }
     }
 }
@@ -184,6 +189,15 @@ impl Environment {
     ///     .with_field_mapping("database_url", "DATABASE_CONNECTION_STRING");
     /// // database_url will read from DATABASE_CONNECTION_STRING instead of APP_DATABASE_URL
     /// ```
+    ///
+    /// The field name may be a dotted path to target a nested field:
+    ///
+    /// ```rust
+    /// use gonfig::Environment;
+    ///
+    /// let env = Environment::new().with_field_mapping("database.host", "APP_DATABASE_HOST");
+    /// // APP_DATABASE_HOST is collected as {"database": {"host": ...}}
+    /// ```
     pub fn with_field_mapping(
         mut self,
         field_name: impl Into<String>,
@@ -194,6 +208,76 @@ impl Environment {
         self
     }
 
+    /// Also read a mapped field from `env_key` when its mapped variable is
+    /// unset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{ConfigSource, Environment};
+    ///
+    /// let env = Environment::new()
+    ///     .with_field_mapping("storage.kind", "APP_STORAGE_KIND")
+    ///     .with_field_alias("storage.kind", "APP_STORAGE__KIND")
+    ///     .override_with("APP_STORAGE__KIND", "local");
+    /// assert_eq!(env.collect()?["storage"]["kind"], "local");
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn with_field_alias(
+        mut self,
+        field_name: impl Into<String>,
+        env_key: impl Into<String>,
+    ) -> Self {
+        self.field_aliases
+            .entry(field_name.into())
+            .or_default()
+            .push(env_key.into());
+        self
+    }
+
+    /// The name and value of the variable set for the mapped field
+    /// `field_name`, trying its mapped variable before its aliases.
+    fn mapped_var(&self, field_name: &str, env_key: &str) -> Option<(String, String)> {
+        let aliases = self.field_aliases.get(field_name).into_iter().flatten();
+        std::iter::once(env_key)
+            .chain(aliases.map(String::as_str))
+            .find_map(|key| {
+                let value = match self.overrides.get(key) {
+                    Some(value) => Some(value.clone()),
+                    None => env::var(key).ok(),
+                };
+                value.map(|v| (key.to_string(), v))
+            })
+    }
+
+    /// Keep the value of a mapped field as the raw string, instead of parsing
+    /// it as a boolean, number or JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gonfig::{ConfigSource, Environment};
+    ///
+    /// let env = Environment::new()
+    ///     .with_field_mapping("version", "APP_VERSION")
+    ///     .with_raw_field("version")
+    ///     .override_with("APP_VERSION", "1.10");
+    /// assert_eq!(env.collect()?["version"], "1.10");
+    /// # Ok::<(), gonfig::Error>(())
+    /// ```
+    pub fn with_raw_field(mut self, field_name: impl Into<String>) -> Self {
+        self.raw_fields.insert(field_name.into());
+        self
+    }
+
+    /// Read a single environment variable by its exact name, honouring overrides.
+    pub(crate) fn lookup(&self, env_key: &str) -> Option<Value> {
+        match self.overrides.get(env_key) {
+            Some(value) => Some(Self::parse_env_value(value)),
+            None => env::var(env_key).ok().map(|v| Self::parse_env_value(&v)),
+        }
+    }
+
     fn build_env_key(&self, path: &[&str]) -> String {
         let mut parts = Vec::new();
 
@@ -242,6 +326,9 @@ impl Environment {
         json!(value)
     }
 
+    #[deprecated(
+        note = "derived structs compose env names through `schema::StructSchema::field_mappings`; use `with_field_mapping` instead"
+    )]
     pub fn collect_for_struct(
         &self,
         struct_name: &str,
@@ -360,13 +447,19 @@ impl ConfigSource for Environment {
             // Use field mappings when available
             let mut result = Map::new();
 
-            // First collect using field mappings
-            for (field_name, env_key) in &self.field_mappings {
+            // First collect using field mappings, sections before their
+            // fields so that a field's variable overrides the section's object
+            let mut mappings: Vec<_> = self.field_mappings.iter().collect();
+            mappings.sort_by_key(|(field_name, _)| field_name.matches('.').count());
+            for (field_name, env_key) in mappings {
                 // Check overrides first, then environment
-                if let Some(override_value) = self.overrides.get(env_key) {
-                    result.insert(field_name.clone(), Self::parse_env_value(override_value));
-                } else if let Ok(value) = env::var(env_key) {
-                    result.insert(field_name.clone(), Self::parse_env_value(&value));
+                if let Some((_, value)) = self.mapped_var(field_name, env_key) {
+                    let value = if self.raw_fields.contains(field_name) {
+                        Value::String(value)
+                    } else {
+                        Self::parse_env_value(&value)
+                    };
+                    path::insert(&mut result, field_name, value);
                 }
             }
 
@@ -385,9 +478,9 @@ impl ConfigSource for Environment {
                         key.to_uppercase()
                     };
 
-                    if key_check.starts_with(&prefix_str)
-                        && !self.field_mappings.values().any(|v| v == &key)
-                    {
+                    let mapped = self.field_mappings.values().any(|v| v == &key)
+                        || self.field_aliases.values().flatten().any(|v| v == &key);
+                    if key_check.starts_with(&prefix_str) && !mapped {
                         let trimmed =
                             key_check[prefix_str.len()..].trim_start_matches(&self.separator);
                         let field_name = trimmed.to_lowercase();
@@ -422,4 +515,15 @@ impl ConfigSource for Environment {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn origin(&self, path: &str) -> Option<String> {
+        let env_key = match self.field_mappings.get(path) {
+            Some(env_key) => self
+                .mapped_var(path, env_key)
+                .map(|(key, _)| key)
+                .unwrap_or_else(|| env_key.clone()),
+            None => self.build_env_key(&path.split('.').collect::<Vec<_>>()),
+        };
+        Some(format!("env var {}", env_key))
+    }
 }
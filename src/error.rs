@@ -133,6 +133,36 @@ pub enum Error {
     /// ```
     #[error("Validation error: {0}")]
     Validation(String),
+
+    /// The configuration requested with `--print-config`, rendered in the
+    /// requested format with secrets redacted.
+    ///
+    /// This is not a failure: like clap's `--help`, it is returned instead of
+    /// the configuration so that the program can print it and exit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use gonfig::{ConfigBuilder, Error, Gonfig};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Gonfig)]
+    /// #[Gonfig(allow_cli)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// match Config::from_gonfig() {
+    ///     Ok(config) => println!("listening on {}", config.port),
+    ///     Err(Error::PrintConfig(rendered)) => {
+    ///         println!("{}", rendered);
+    ///         std::process::exit(0);
+    ///     }
+    ///     Err(e) => eprintln!("{}", e),
+    /// }
+    /// ```
+    #[error("{0}")]
+    PrintConfig(String),
 }
 
 /// Type alias for `Result<T, gonfig::Error>`.
@@ -0,0 +1,641 @@
+use crate::{
+    config::{without_nulls, ConfigFiles, ConfigFormat},
+    error::{Error, Result},
+    schema::{EnumTagging, FieldSchema, StructSchema, ValueKind},
+    source::Source,
+};
+use clap::Command;
+use roff::{bold, roman, Roff};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::io::Write;
+
+pub use clap_complete::Shell;
+
+/// Write the completion script of `command` for `shell` to `out`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gonfig::generate::{completions, Shell};
+/// use gonfig::{Cli, Gonfig, GonfigSchema};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Gonfig)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let mut script = Vec::new();
+/// completions(Cli::command(&Config::gonfig_schema()).name("app"), Shell::Bash, &mut script);
+/// assert!(String::from_utf8(script).unwrap().contains("--port"));
+/// ```
+pub fn completions(mut command: Command, shell: Shell, out: &mut dyn Write) {
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+/// A roff man page for a command, with ENVIRONMENT and FILES sections.
+///
+/// The usual sections come from the clap command; the environment variables
+/// and configuration files are listed from the schema and [`ConfigFiles`].
+///
+/// # Examples
+///
+/// ```rust
+/// use gonfig::generate::ManPage;
+/// use gonfig::{Cli, Gonfig, GonfigSchema};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Gonfig)]
+/// #[Gonfig(env_prefix = "APP")]
+/// struct Config {
+///     /// Port to listen on
+///     port: u16,
+/// }
+///
+/// let schema = Config::gonfig_schema();
+/// let mut page = Vec::new();
+/// ManPage::new(Cli::command(&schema).name("app"))
+///     .env_vars_from(&schema)
+///     .file("/etc/app.toml", "System-wide configuration")
+///     .render(&mut page)?;
+///
+/// let page = String::from_utf8(page).unwrap();
+/// assert!(page.contains(".SH ENVIRONMENT"));
+/// assert!(page.contains("APP_PORT"));
+/// # Ok::<(), gonfig::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManPage {
+    command: Command,
+    env_vars: Vec<(String, String)>,
+    files: Vec<(String, String)>,
+}
+
+impl ManPage {
+    /// Create a man page for `command`, without environment variables or files.
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            env_vars: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// List the environment variable of every field of `schema`, described
+    /// by the field's doc comment and default.
+    pub fn env_vars_from(mut self, schema: &StructSchema) -> Self {
+        for var in EnvReference::from_schema(schema).vars {
+            let mut description = var
+                .description
+                .unwrap_or_else(|| format!("Sets {}.", var.file_key));
+            if let Some(default) = var.default {
+                description.push_str(&format!(" [default: {}]", default));
+            }
+            self.env_vars.push((var.name, description));
+        }
+        self
+    }
+
+    /// List an environment variable.
+    pub fn env_var(mut self, name: impl Into<String>, description: impl Into<String>) -> Self {
+        self.env_vars.push((name.into(), description.into()));
+        self
+    }
+
+    /// List the default paths of `files`, and the environment variable
+    /// overriding them.
+    pub fn config_files(mut self, files: &ConfigFiles) -> Self {
+        let overridden_by = match (files.cli_flag_name(), files.env_var_name()) {
+            (Some(flag), Some(var)) => format!(", unless --{} or {} is given", flag, var),
+            (Some(flag), None) => format!(", unless --{} is given", flag),
+            (None, Some(var)) => format!(", unless {} is set", var),
+            (None, None) => String::new(),
+        };
+        for path in files.default_paths() {
+            self.files.push((
+                path.display().to_string(),
+                format!("Configuration file{}.", overridden_by),
+            ));
+        }
+
+        if let Some(var) = files.env_var_name() {
+            self.env_vars.push((
+                var.to_string(),
+                "Configuration files to load instead of the default ones, separated like PATH."
+                    .to_string(),
+            ));
+        }
+        self
+    }
+
+    /// List a configuration file.
+    pub fn file(mut self, path: impl Into<String>, description: impl Into<String>) -> Self {
+        self.files.push((path.into(), description.into()));
+        self
+    }
+
+    /// Write the man page to `out`.
+    pub fn render(&self, out: &mut dyn Write) -> Result<()> {
+        let man = clap_mangen::Man::new(self.command.clone());
+        man.render_title(out)?;
+        man.render_name_section(out)?;
+        man.render_synopsis_section(out)?;
+        man.render_description_section(out)?;
+        if self.command.get_arguments().any(|a| !a.is_hide_set()) {
+            man.render_options_section(out)?;
+        }
+        if self.command.get_after_help().is_some() || self.command.get_after_long_help().is_some() {
+            man.render_extra_section(out)?;
+        }
+
+        let mut roff = Roff::default();
+        for (heading, entries) in [("ENVIRONMENT", &self.env_vars), ("FILES", &self.files)] {
+            if entries.is_empty() {
+                continue;
+            }
+            roff.control("SH", [heading]);
+            for (name, description) in entries {
+                roff.control("TP", []);
+                roff.text([bold(name.as_str())]);
+                roff.text([roman(description.as_str())]);
+            }
+        }
+        roff.to_writer(out)?;
+
+        if self.command.get_version().is_some() {
+            man.render_version_section(out)?;
+        }
+        if self.command.get_author().is_some() {
+            man.render_authors_section(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reference documentation of the environment variables a configuration reads.
+///
+/// Built from the same schema as the environment source, so it cannot drift
+/// from the code. Render it with [`EnvReference::to_markdown`],
+/// [`EnvReference::to_json`] or [`EnvReference::to_table`], which is also
+/// its `Display` output.
+///
+/// # Examples
+///
+/// ```rust
+/// use gonfig::generate::EnvReference;
+/// use gonfig::{Gonfig, GonfigSchema};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Gonfig)]
+/// #[Gonfig(env_prefix = "APP")]
+/// struct Config {
+///     /// Port to listen on
+///     #[gonfig(default = "8080")]
+///     port: u16,
+/// }
+///
+/// let reference = EnvReference::from_schema(&Config::gonfig_schema());
+/// assert_eq!(reference.vars[0].name, "APP_PORT");
+/// assert!(reference
+///     .to_markdown()
+///     .contains("| `APP_PORT` | integer | `8080` | Port to listen on | `--port` | `port` |"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EnvReference {
+    /// The variables, in field order.
+    pub vars: Vec<EnvVarDoc>,
+}
+
+/// An environment variable read by a configuration.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnvVarDoc {
+    /// Name of the variable, e.g. `APP_DATABASE_URL`.
+    pub name: String,
+    /// Kind of value, e.g. `integer`.
+    #[serde(rename = "type")]
+    pub type_name: &'static str,
+    /// Default value of the field, from `#[gonfig(default)]`.
+    pub default: Option<String>,
+    /// First paragraph of the field's doc comment.
+    pub description: Option<String>,
+    /// Command-line argument setting the same field, e.g. `--database-url`.
+    pub cli_flag: Option<String>,
+    /// Dotted key of the field in config files, e.g. `database.url`.
+    pub file_key: String,
+}
+
+impl EnvReference {
+    /// List the environment variable of every field of `schema` that may be
+    /// read from the environment.
+    pub fn from_schema(schema: &StructSchema) -> Self {
+        let mut vars: Vec<EnvVarDoc> = Vec::new();
+
+        for mapping in schema.field_mappings() {
+            if mapping.env_key.is_empty()
+                || !mapping.allows(Source::Environment)
+                || vars.iter().any(|v| v.name == mapping.env_key)
+            {
+                continue;
+            }
+
+            let cli_flag = match (mapping.positional, mapping.subcommand) {
+                _ if !mapping.allows(Source::Cli) => None,
+                (Some(_), _) => Some(format!(
+                    "<{}>",
+                    mapping.cli_key.to_uppercase().replace('-', "_")
+                )),
+                (None, Some(subcommand)) => Some(format!("{} --{}", subcommand, mapping.cli_key)),
+                (None, None) => Some(format!("--{}", mapping.cli_key)),
+            };
+            vars.push(EnvVarDoc {
+                name: mapping.env_key,
+                type_name: mapping.kind.type_name(),
+                default: mapping.default_value.as_ref().map(display_default),
+                description: mapping.doc.map(|doc| {
+                    doc.split("\n\n")
+                        .next()
+                        .unwrap_or_default()
+                        .replace('\n', " ")
+                }),
+                cli_flag,
+                file_key: mapping.path,
+            });
+        }
+        Self { vars }
+    }
+
+    /// Don't list CLI flags, for configurations that do not parse the
+    /// command line.
+    pub fn without_cli_flags(mut self) -> Self {
+        for var in &mut self.vars {
+            var.cli_flag = None;
+        }
+        self
+    }
+
+    /// Render a Markdown table, one row per variable.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from(
+            "| Variable | Type | Default | Description | CLI flag | File key |\n\
+             |----------|------|---------|-------------|----------|----------|\n",
+        );
+        let code = |s: &str| format!("`{}`", s);
+        for var in &self.vars {
+            let cells = [
+                code(&var.name),
+                var.type_name.to_string(),
+                var.default.as_deref().map(code).unwrap_or_default(),
+                var.description.clone().unwrap_or_default(),
+                var.cli_flag.as_deref().map(code).unwrap_or_default(),
+                code(&var.file_key),
+            ];
+            let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        out
+    }
+
+    /// Render a pretty-printed JSON array, one object per variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Serialization`] if the reference cannot be serialized.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.vars).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Render a plain-text table with aligned columns, for terminals.
+    pub fn to_table(&self) -> String {
+        let mut rows = vec![[
+            "VARIABLE".to_string(),
+            "TYPE".to_string(),
+            "DEFAULT".to_string(),
+            "CLI FLAG".to_string(),
+            "FILE KEY".to_string(),
+            "DESCRIPTION".to_string(),
+        ]];
+        for var in &self.vars {
+            rows.push([
+                var.name.clone(),
+                var.type_name.to_string(),
+                var.default.clone().unwrap_or_else(|| "-".to_string()),
+                var.cli_flag.clone().unwrap_or_else(|| "-".to_string()),
+                var.file_key.clone(),
+                var.description.clone().unwrap_or_default(),
+            ]);
+        }
+
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+        for row in &rows {
+            let line: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            out.push_str(line.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl fmt::Display for EnvReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_table())
+    }
+}
+
+/// A default as users would type it, without the quotes of strings.
+fn display_default(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// An annotated starter config file for `schema` in `format`.
+///
+/// Every key is present with its default value, or a placeholder such as
+/// `""` or `0` when it has none. Doc comments become comments, nested structs
+/// become sections, and enums show their first variant with the others listed
+/// in a comment. JSON has no comments, so JSON output only has the keys and
+/// their values.
+///
+/// # Examples
+///
+/// ```rust
+/// use gonfig::generate::template;
+/// use gonfig::{ConfigFormat, Gonfig, GonfigSchema};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Gonfig)]
+/// struct Config {
+///     /// Port to listen on
+///     #[gonfig(default = "8080")]
+///     port: u16,
+///     database: Database,
+/// }
+///
+/// #[derive(Deserialize, Gonfig)]
+/// struct Database {
+///     url: String,
+/// }
+///
+/// assert_eq!(
+///     template(&Config::gonfig_schema(), ConfigFormat::Toml),
+///     "# Port to listen on\nport = 8080\n\n[database]\nurl = \"\"\n"
+/// );
+/// ```
+pub fn template(schema: &StructSchema, format: ConfigFormat) -> String {
+    let mut entries = template_entries(&schema.fields, &schema.defaults());
+    if schema.is_enum() {
+        entries = enum_entries(schema, "", None)
+            .map(|entry| match entry.body {
+                TemplateBody::Section(entries) => entries,
+                TemplateBody::Value(_) => Vec::new(),
+            })
+            .unwrap_or_default();
+    }
+    let header = schema
+        .about
+        .map(|about| about.split("\n\n").next().unwrap_or_default().to_string());
+
+    let mut out = String::new();
+    match format {
+        ConfigFormat::Toml | ConfigFormat::Yaml => {
+            write_comment(&mut out, header.as_deref(), "", "#");
+            if header.is_some() && !entries.is_empty() {
+                out.push('\n');
+            }
+            if format == ConfigFormat::Toml {
+                write_toml(&mut out, &entries, "");
+            } else {
+                write_yaml(&mut out, &entries, 0);
+            }
+        }
+        ConfigFormat::Json => {
+            out.push_str("{\n");
+            write_json(&mut out, &entries, 1);
+            out.push_str("}\n");
+        }
+    }
+    out
+}
+
+/// A key of a config template, with its doc comment.
+struct TemplateEntry {
+    key: String,
+    doc: Option<String>,
+    body: TemplateBody,
+}
+
+enum TemplateBody {
+    Value(Value),
+    Section(Vec<TemplateEntry>),
+}
+
+/// Entries of `fields`, with values from `defaults`, the defaults object at
+/// their level as built by [`StructSchema::defaults`].
+fn template_entries(fields: &[FieldSchema], defaults: &Value) -> Vec<TemplateEntry> {
+    let mut entries = Vec::new();
+    for field in fields {
+        let doc = field.doc.map(str::to_string);
+        let default = defaults.get(field.name).filter(|v| !v.is_null());
+
+        match (&field.nested, default) {
+            // Each subcommand has its own section, named after it
+            (Some(nested), _) if field.subcommand => {
+                for variant in nested.variants.iter().filter(|v| !v.fields.is_empty()) {
+                    let section = defaults.get(variant.name).unwrap_or(&Value::Null);
+                    entries.push(TemplateEntry {
+                        key: variant.name.to_string(),
+                        doc: variant.about.map(str::to_string),
+                        body: TemplateBody::Section(template_entries(&variant.fields, section)),
+                    });
+                }
+            }
+            (Some(nested), None) if nested.is_enum() => {
+                entries.extend(enum_entries(nested, field.name, doc));
+            }
+            (Some(nested), _) if !nested.is_enum() => entries.push(TemplateEntry {
+                key: field.name.to_string(),
+                doc,
+                body: TemplateBody::Section(template_entries(
+                    &nested.fields,
+                    default.unwrap_or(&Value::Null),
+                )),
+            }),
+            _ => entries.push(TemplateEntry {
+                key: field.name.to_string(),
+                doc,
+                body: TemplateBody::Value(
+                    default.cloned().unwrap_or_else(|| placeholder(field.kind)),
+                ),
+            }),
+        }
+    }
+    entries
+}
+
+/// The entry of an enum field set to its first variant, or `None` for an
+/// enum without variants.
+fn enum_entries(schema: &StructSchema, key: &str, doc: Option<String>) -> Option<TemplateEntry> {
+    let variant = schema.variants.first()?;
+    let names: Vec<&str> = schema.variants.iter().map(|v| v.name).collect();
+    let one_of = format!("One of: {}", names.join(", "));
+    let doc = Some(match doc {
+        Some(doc) => format!("{}\n\n{}", doc, one_of),
+        None => one_of,
+    });
+
+    let selector = |tag: &str| TemplateEntry {
+        key: tag.to_string(),
+        doc: None,
+        body: TemplateBody::Value(Value::String(variant.name.to_string())),
+    };
+    let variant_defaults = StructSchema {
+        fields: variant.fields.clone(),
+        ..StructSchema::default()
+    }
+    .defaults();
+    let fields = template_entries(&variant.fields, &variant_defaults);
+
+    let body = match schema.tagging {
+        EnumTagging::External if fields.is_empty() => {
+            TemplateBody::Value(Value::String(variant.name.to_string()))
+        }
+        EnumTagging::External => TemplateBody::Section(vec![TemplateEntry {
+            key: variant.name.to_string(),
+            doc: None,
+            body: TemplateBody::Section(fields),
+        }]),
+        EnumTagging::Internal { tag } => {
+            TemplateBody::Section(std::iter::once(selector(tag)).chain(fields).collect())
+        }
+        EnumTagging::Adjacent { tag, content } => {
+            let mut entries = vec![selector(tag)];
+            if !fields.is_empty() {
+                entries.push(TemplateEntry {
+                    key: content.to_string(),
+                    doc: None,
+                    body: TemplateBody::Section(fields),
+                });
+            }
+            TemplateBody::Section(entries)
+        }
+        EnumTagging::Untagged => TemplateBody::Section(fields),
+    };
+    Some(TemplateEntry {
+        key: key.to_string(),
+        doc,
+        body,
+    })
+}
+
+/// Value shown for a field without a default.
+fn placeholder(kind: ValueKind) -> Value {
+    match kind {
+        ValueKind::Bool => Value::Bool(false),
+        ValueKind::Integer => Value::from(0),
+        ValueKind::Float => Value::from(0.0),
+        ValueKind::List => Value::Array(Vec::new()),
+        ValueKind::String | ValueKind::Path | ValueKind::Any => Value::String(String::new()),
+    }
+}
+
+fn write_comment(out: &mut String, doc: Option<&str>, indent: &str, marker: &str) {
+    for line in doc.into_iter().flat_map(str::lines) {
+        let line = format!("{} {}", marker, line);
+        out.push_str(indent);
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+}
+
+fn write_toml(out: &mut String, entries: &[TemplateEntry], table: &str) {
+    // Keys of a table must come before its sub-tables
+    for (i, entry) in entries.iter().enumerate() {
+        if let TemplateBody::Value(value) = &entry.body {
+            if i > 0 && entry.doc.is_some() {
+                out.push('\n');
+            }
+            write_comment(out, entry.doc.as_deref(), "", "#");
+            let value = toml::Value::try_from(without_nulls(value))
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| "\"\"".to_string());
+            out.push_str(&format!("{} = {}\n", entry.key, value));
+        }
+    }
+
+    for entry in entries {
+        if let TemplateBody::Section(children) = &entry.body {
+            let path = match table {
+                "" => entry.key.clone(),
+                table => format!("{}.{}", table, entry.key),
+            };
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            write_comment(out, entry.doc.as_deref(), "", "#");
+            out.push_str(&format!("[{}]\n", path));
+            write_toml(out, children, &path);
+        }
+    }
+}
+
+fn write_yaml(out: &mut String, entries: &[TemplateEntry], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 && (entry.doc.is_some() || depth == 0 && is_section(entry)) {
+            out.push('\n');
+        }
+        write_comment(out, entry.doc.as_deref(), &indent, "#");
+        match &entry.body {
+            TemplateBody::Value(value) => {
+                out.push_str(&format!("{}{}: {}\n", indent, entry.key, value));
+            }
+            TemplateBody::Section(children) if children.is_empty() => {
+                out.push_str(&format!("{}{}: {{}}\n", indent, entry.key));
+            }
+            TemplateBody::Section(children) => {
+                out.push_str(&format!("{}{}:\n", indent, entry.key));
+                write_yaml(out, children, depth + 1);
+            }
+        }
+    }
+}
+
+fn write_json(out: &mut String, entries: &[TemplateEntry], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for (i, entry) in entries.iter().enumerate() {
+        let key = Value::String(entry.key.clone());
+        match &entry.body {
+            TemplateBody::Value(value) => {
+                out.push_str(&format!("{}{}: {}", indent, key, value));
+            }
+            TemplateBody::Section(children) if children.is_empty() => {
+                out.push_str(&format!("{}{}: {{}}", indent, key));
+            }
+            TemplateBody::Section(children) => {
+                out.push_str(&format!("{}{}: {{\n", indent, key));
+                write_json(out, children, depth + 1);
+                out.push_str(&format!("{}}}", indent));
+            }
+        }
+        out.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+    }
+}
+
+fn is_section(entry: &TemplateEntry) -> bool {
+    matches!(entry.body, TemplateBody::Section(_))
+}
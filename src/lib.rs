@@ -164,26 +164,137 @@
 //!
 //! ### Struct-level attributes:
 //! - `#[Gonfig(env_prefix = "PREFIX")]` - Set environment variable prefix
+//! - `#[Gonfig(allow_env = false)]` - Disable environment variables (enabled by default)
 //! - `#[Gonfig(allow_cli)]` - Enable CLI argument support
+//! - `#[Gonfig(allow_set)]` - Accept `--set path=value` and `--set-json path=<json>`
+//!   overrides of any setting (see [`Cli::allow_set`])
 //! - `#[Gonfig(allow_config)]` - Enable config file support
+//! - `#[Gonfig(config_file = "myapp.toml")]` - Config files to load instead of `config.{toml,yaml,json}`;
+//!   several can be listed, separated by commas
+//! - `#[Gonfig(config_file_env = "MYAPP_CONFIG")]` - Environment variable overriding the paths
+//! - `#[Gonfig(config_file_cli = "config")]` - CLI flag overriding the paths
+//! - `#[Gonfig(config_optional)]` - Skip `config_file` paths that do not exist
+//!   (see [`ConfigFiles`])
+//! - `#[Gonfig(default)]` - Take field defaults from the struct's `Default` impl,
+//!   below field-level defaults
+//! - `#[Gonfig(rename_all_env = "SCREAMING_SNAKE_CASE")]` and
+//!   `#[Gonfig(rename_all_cli = "kebab-case")]` - Casing of environment variable names
+//!   and CLI flags, with the rules of serde's `rename_all`; those are the defaults
+//!
+//! Keys follow serde's `rename` and `rename_all`, and environment variables and
+//! flags are derived from the serialized name: a field serialized as `dbUrl`
+//! reads `APP_DB_URL` and `--db-url`.
 //!
 //! ### Field-level attributes:
 //! - `#[gonfig(env_name = "CUSTOM_NAME")]` - Override environment variable name
 //! - `#[gonfig(cli_name = "custom-name")]` - Override CLI argument name
-//! - `#[skip]` or `#[skip_gonfig]` - Skip this field from all configuration sources
+//! - `#[gonfig(short = 'p')]` - Single-character CLI flag
+//! - `#[gonfig(default = "8080")]` - Default value, parsed as JSON or used as a string
+//! - `#[gonfig(default)]` - Default from `Default::default()` of the field's type
+//! - `#[gonfig(default_fn = "path::to::fn")]` - Default returned by `fn() -> T`
+//! - `#[gonfig(parse_with = "path::to::fn")]` - Parse the value with `fn(&str)` or
+//!   `fn(&serde_json::Value)` returning `Result<T, E: Display>`, where `T: Serialize`;
+//!   environment variables and CLI flags pass the raw string
+//! - `#[gonfig(sources = "file,env")]` - Only read the field from the listed sources
+//!   (`env`, `file`, `cli`); values from other sources are ignored with a warning
+//! - `#[gonfig(no_env)]` - Never read the field from environment variables
+//! - `#[gonfig(alias = "old_name")]` - Former field name, read from every source
+//!   when the current name is absent
+//! - `#[gonfig(deprecated_env = "LEGACY_VAR")]` - Former exact environment variable name
+//! - `#[gonfig(deprecated_key = "old_key")]` - Former config file key
+//! - `#[gonfig(sensitive)]` - Show the value as `<redacted>` in `--print-config` output
+//! - `#[gonfig(subcommand)]` - Select the field's enum variant with a CLI subcommand
+//! - `#[gonfig(positional)]` or `#[gonfig(positional(index = 0))]` - Read the field
+//!   from a positional argument; a `Vec` field takes every remaining argument
+//! - `#[skip]` or `#[skip_gonfig]` - Skip this field from all configuration sources;
+//!   it cannot be combined with other options
+//!
+//! Using a former name logs a warning naming the replacement;
+//! [`ConfigBuilder::deny_deprecated`] turns it into an error.
+//!
+//! Literal defaults are checked against the field type at compile time for
+//! integers (in the field's own width), floats, `bool`, `char`, `String`,
+//! `PathBuf`, lists and `Option`, against the unit variants of enums that
+//! derive `Gonfig`, and against `one_of` when it is given, so a typo fails the
+//! build:
+//!
+//! ```compile_fail
+//! use gonfig::Gonfig;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Gonfig)]
+//! struct Config {
+//!     #[gonfig(default = "80x")] // error: invalid default `80x`: expected an integer that fits in `u16`
+//!     port: u16,
+//! }
+//! ```
+//!
+//! With `allow_cli`, doc comments become the `--help` text of the generated
+//! `clap::Command`; see [`Cli::command`]. `from_gonfig()` reads the process
+//! arguments leniently, ignoring those that are not fields, while
+//! `from_gonfig_with_args` parses them with the command, rejecting unknown
+//! flags. The command also accepts `--config <path>`, repeatable, when config
+//! files are read, and `--print-config [json|yaml|toml]`, which returns the
+//! redacted configuration as [`Error::PrintConfig`] for the program to print.
+//!
+//! ### Validation attributes:
+//! - `#[gonfig(range(min = 1, max = 65535))]` - Numeric bounds, inclusive
+//! - `#[gonfig(length(min = 1, max = 64))]` - Length of a string, list or map
+//! - `#[gonfig(regex = "^[a-z]+$")]` - String must match the pattern
+//! - `#[gonfig(one_of = ["debug", "info"])]` - Value must be one of the listed literals
+//! - `#[gonfig(url)]` - String must be an absolute URL
+//! - `#[gonfig(non_empty)]` - String, list or map must not be empty
+//! - `#[gonfig(custom = "path::to::fn")]` - Call `fn(&FieldType) -> Result<(), E: Display>`
+//!
+//! Invalid `regex` patterns and `one_of` integers that do not fit the field
+//! fail the build.
+//!
+//! Validation runs after deserialization and reports every failing field with
+//! its path and the source that set it, e.g.
+//! `port: must be between 1 and 65535 (got 70000) (set by env var APP_PORT)`.
+//!
+//! ### Cross-field constraints:
+//! - `#[gonfig(required_if = "enable_tls")]` - Required when `enable_tls` is set and not `false`;
+//!   `required_if = "mode=tls"` compares against a value
+//! - `#[gonfig(conflicts_with = "port")]` - Must not be set together with `port`
+//! - `#[gonfig(requires = "tls_key")]` - When set, `tls_key` must be set too
+//!
+//! Constraints are checked on the merged configuration and name the sources
+//! that set the offending values. The same rules are available on
+//! [`ConfigBuilder`] as `required_if`, `conflicts_with` and `requires`.
+//!
+//! ### Enums
+//!
+//! Enums can derive `Gonfig` to select a variant by name. The variant name is
+//! matched case-insensitively against its serde name, and an unknown name fails
+//! with an error listing the allowed variants. With `#[serde(tag = "kind")]` the
+//! selector and the variant's fields get their own names, e.g. `APP_STORAGE_KIND`
+//! (or `APP_STORAGE__KIND`, like a section key) and `APP_STORAGE_BUCKET`; with
+//! `content = "config"` the fields live under `APP_STORAGE_CONFIG_*`. Externally
+//! tagged enums are selected with the field's own name (`APP_STORAGE=local`).
+//!
+//! A `#[gonfig(subcommand)]` enum field makes each variant a subcommand whose
+//! settings live in a section named after it: `app serve --port 80`,
+//! `[serve]` in files and `APP_SERVE__PORT` in the environment. Top-level
+//! settings fill the variant's fields of the same name unless its section sets
+//! them.
 //!
 //! ## Environment Variable Naming
 //!
 //! Environment variables follow a consistent hierarchical pattern:
 //!
-//! - **With prefix**: `{PREFIX}_{STRUCT_NAME}_{FIELD_NAME}`
-//!   - Example: prefix `APP`, struct `Config`, field `database_url` → `APP_CONFIG_DATABASE_URL`
-//! - **Without prefix**: `{STRUCT_NAME}_{FIELD_NAME}`
-//!   - Example: struct `Config`, field `database_url` → `CONFIG_DATABASE_URL`
+//! - **With prefix**: `{PREFIX}_{FIELD_NAME}`
+//!   - Example: prefix `APP`, field `database_url` → `APP_DATABASE_URL`
+//! - **Without prefix**: `{FIELD_NAME}`
+//!   - Example: field `database_url` → `DATABASE_URL`
 //! - **With field override**: Uses the exact override value
 //!   - Example: `#[gonfig(env_name = "DB_URL")]` → `DB_URL`
-//! - **Nested structs**: Each level adds to the path
-//!   - Example: `APP_PARENT_CHILD_FIELD`
+//! - **Nested structs**: Fields whose type also derives `Gonfig` add their name to the path
+//!   - Example: prefix `APP`, field `database` with field `host` → `APP_DATABASE_HOST`
+//!   - CLI flags follow the same path: `--database-host`
+//!   - An `env_name` on the nested field replaces its part of the path: `DB` → `DB_HOST`
+//!   - The `env_prefix` of a nested struct only applies when it is loaded on its own
+//!   - An `Option` of a nested struct stays `None` unless one of its fields is set
 
 /// Configuration builder for assembling multiple configuration sources.
 ///
@@ -221,6 +332,33 @@ pub mod error;
 /// through the [`MergeStrategy`] enum and related types.
 pub mod merge;
 
+/// Shell completions, man pages, config templates and environment variable
+/// references.
+///
+/// Types deriving `Gonfig` with `allow_cli` get `gonfig_completions` and
+/// `gonfig_man_page` functions built on [`completions`](generate::completions)
+/// and [`ManPage`](generate::ManPage), so packaging scripts can produce both
+/// artifacts from the configuration struct. Every derived type also gets
+/// `template`, a starter config file from [`template`](generate::template),
+/// and `env_reference`, an [`EnvReference`](generate::EnvReference) that
+/// renders as Markdown, JSON or a plain-text table.
+pub mod generate;
+
+mod path;
+
+/// Runtime description of derived configuration structs.
+///
+/// The [`GonfigSchema`] trait is implemented by `#[derive(Gonfig)]` and is used
+/// to compose environment variable and CLI names for nested structs.
+pub mod schema;
+
+/// Declarative field validation rules.
+///
+/// Provides [`FieldValidator`](validation::FieldValidator), the rules behind the
+/// `range`, `length`, `regex`, `one_of`, `url`, `non_empty` and `custom`
+/// field attributes, and the cross-field [`Constraint`](validation::Constraint)s.
+pub mod validation;
+
 /// Core traits and types for configuration sources.
 ///
 /// Defines the [`ConfigSource`] trait that all configuration sources implement
@@ -229,13 +367,18 @@ pub mod source;
 
 pub use gonfig_derive::Gonfig;
 
+/// The clap version used by [`Cli::command`], re-exported for naming its types.
+pub use clap;
+
 pub use builder::ConfigBuilder;
 pub use cli::Cli;
-pub use config::{Config, ConfigFormat};
+pub use config::{Config, ConfigFiles, ConfigFormat};
 pub use environment::Environment;
 pub use error::{Error, Result};
 pub use merge::MergeStrategy;
+pub use schema::GonfigSchema;
 pub use source::{ConfigSource, Source};
+pub use validation::Diagnostics;
 
 /// A configuration prefix used for environment variables
 #[derive(Debug, Clone, Default)]
@@ -0,0 +1,127 @@
+//! Helpers for addressing values inside nested configuration objects.
+
+use serde_json::{Map, Value};
+
+/// Insert `value` at a dotted `path` (e.g. `database.host`), creating
+/// intermediate objects and replacing non-object values along the way.
+pub(crate) fn insert(map: &mut Map<String, Value>, path: &str, value: Value) {
+    match path.split_once('.') {
+        None => {
+            map.insert(path.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let child = map
+                .entry(head.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !child.is_object() {
+                *child = Value::Object(Map::new());
+            }
+            if let Value::Object(child_map) = child {
+                insert(child_map, rest, value);
+            }
+        }
+    }
+}
+
+/// Look up the value at a dotted `path`.
+pub(crate) fn get<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, part| current.get(part))
+}
+
+/// Remove and return the value at a dotted `path`.
+pub(crate) fn remove(value: &mut Value, path: &str) -> Option<Value> {
+    match path.rsplit_once('.') {
+        None => value.as_object_mut()?.remove(path),
+        Some((parent, last)) => parent
+            .split('.')
+            .try_fold(value, |current, part| current.get_mut(part))?
+            .as_object_mut()?
+            .remove(last),
+    }
+}
+
+/// Set `new` at a `path` that may index into arrays, e.g. `servers[0].port`.
+///
+/// Intermediate objects and arrays are created as needed; arrays shorter than
+/// an index are padded with `null`.
+pub(crate) fn set_indexed(value: &mut Value, path: &str, new: Value) -> Result<(), String> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut indices) = match part.find('[') {
+            Some(open) => (&part[..open], &part[open..]),
+            None => (part, ""),
+        };
+        if key.is_empty() && (segments.is_empty() || indices.is_empty()) {
+            return Err(format!("invalid path '{}'", path));
+        }
+        if !key.is_empty() {
+            segments.push(Segment::Key(key));
+        }
+        while !indices.is_empty() {
+            let index = indices
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .and_then(|(index, rest)| Some((index.parse::<usize>().ok()?, rest)));
+            let Some((index, rest)) = index else {
+                return Err(format!("invalid array index in path '{}'", path));
+            };
+            segments.push(Segment::Index(index));
+            indices = rest;
+        }
+    }
+
+    let mut current = value;
+    for segment in segments {
+        current = match segment {
+            Segment::Key(key) => {
+                if !current.is_object() {
+                    *current = Value::Object(Map::new());
+                }
+                let Value::Object(map) = current else {
+                    unreachable!()
+                };
+                map.entry(key).or_insert(Value::Null)
+            }
+            Segment::Index(index) => {
+                if !current.is_array() {
+                    *current = Value::Array(Vec::new());
+                }
+                let Value::Array(items) = current else {
+                    unreachable!()
+                };
+                if items.len() <= index {
+                    items.resize(index + 1, Value::Null);
+                }
+                &mut items[index]
+            }
+        };
+    }
+    *current = new;
+    Ok(())
+}
+
+/// Whether `path` indexes into an array, e.g. `servers[0].port`.
+pub(crate) fn has_index(path: &str) -> bool {
+    path.contains('[')
+}
+
+/// `path` without array indices, e.g. `servers.port` for `servers[0].port`.
+pub(crate) fn without_indices(path: &str) -> String {
+    let mut result = String::new();
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
@@ -0,0 +1,1551 @@
+//! Runtime description of structs and enums deriving [`Gonfig`](crate::Gonfig).
+//!
+//! The derive macro only sees the type it is applied to, so it cannot know
+//! how a nested field's type is configured. Instead every derived type
+//! describes itself through [`GonfigSchema`], and the parent walks the nested
+//! schemas at runtime to compose environment variable names, CLI flags and
+//! default values for every leaf field.
+
+use crate::{
+    error::{Error, Result},
+    merge::MergeStrategy,
+    source::Source,
+    validation::{Constraint, FieldConstraint, FieldError, FieldValidator},
+};
+use serde_json::{Map, Value};
+
+/// Replacement for the values of sensitive fields in printed configuration.
+const REDACTED: &str = "<redacted>";
+
+/// Description of a struct or enum deriving `Gonfig`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gonfig::schema::{FieldSchema, StructSchema};
+///
+/// let database = StructSchema::new("Database")
+///     .field(FieldSchema::new("host").default_value("\"localhost\""))
+///     .field(FieldSchema::new("port"));
+///
+/// let schema = StructSchema::new("Config")
+///     .env_prefix("APP")
+///     .field(FieldSchema::new("database").nested(Some(database)));
+///
+/// let mappings = schema.field_mappings();
+/// assert_eq!(mappings[0].path, "database.host");
+/// assert_eq!(mappings[0].env_key, "APP_DATABASE_HOST");
+/// assert_eq!(mappings[0].cli_key, "database-host");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StructSchema {
+    /// Rust name of the struct.
+    pub name: &'static str,
+    /// Prefix applied to environment variables when this struct is loaded on its own.
+    pub env_prefix: Option<&'static str>,
+    /// Fields that take part in configuration (skipped fields are omitted).
+    pub fields: Vec<FieldSchema>,
+    /// Variants of an enum; empty for structs.
+    pub variants: Vec<VariantSchema>,
+    /// How an enum's variant is represented, mirroring serde's enum representations.
+    pub tagging: EnumTagging,
+    /// Doc comment of the type, used as the description of generated commands.
+    pub about: Option<&'static str>,
+    /// Defaults of every field at once, from `#[Gonfig(default)]` and the
+    /// type's `Default` implementation.
+    pub default_fn: Option<fn() -> Value>,
+}
+
+/// Description of an enum variant.
+#[derive(Debug, Clone, Default)]
+pub struct VariantSchema {
+    /// Serialized name of the variant.
+    pub name: &'static str,
+    /// Fields carried by the variant (empty for unit variants).
+    pub fields: Vec<FieldSchema>,
+    /// Doc comment of the variant, or of the type it carries, used as the
+    /// description of its subcommand.
+    pub about: Option<&'static str>,
+}
+
+/// Serde representation of an enum, which decides where the variant selector lives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumTagging {
+    /// `"local"` or `{"s3": {...}}` (serde's default).
+    #[default]
+    External,
+    /// `{"kind": "s3", ...fields}` from `#[serde(tag = "kind")]`.
+    Internal { tag: &'static str },
+    /// `{"kind": "s3", "config": {...}}` from `#[serde(tag = "kind", content = "config")]`.
+    Adjacent {
+        tag: &'static str,
+        content: &'static str,
+    },
+    /// `#[serde(untagged)]`; the variant cannot be selected by name.
+    Untagged,
+}
+
+/// Description of a single configurable field.
+#[derive(Debug, Clone, Default)]
+pub struct FieldSchema {
+    /// Key of the field in the merged configuration.
+    pub name: &'static str,
+    /// Exact environment variable name from `#[gonfig(env_name = "...")]`.
+    pub env_name: Option<&'static str>,
+    /// Exact CLI flag name from `#[gonfig(cli_name = "...")]`.
+    pub cli_name: Option<&'static str>,
+    /// Part of the environment variable contributed by the field, joined to
+    /// the parent's prefix. `None` uses `name` in upper case.
+    pub env_segment: Option<&'static str>,
+    /// Part of the CLI flag contributed by the field, joined to the parent's
+    /// flag. `None` uses `name` with `_` replaced by `-`.
+    pub cli_segment: Option<&'static str>,
+    /// Raw default from `#[gonfig(default = "...")]`.
+    pub default: Option<&'static str>,
+    /// Computed default from `#[gonfig(default_fn = "...")]` or
+    /// `#[gonfig(default)]`, used when there is no raw default.
+    pub default_fn: Option<fn() -> Value>,
+    /// Schema of the field's type when it also derives `Gonfig`.
+    pub nested: Option<StructSchema>,
+    /// Rules checked against the field's value, from validation attributes.
+    pub validators: Vec<FieldValidator>,
+    /// Rules relating the field to its siblings, from `required_if`,
+    /// `conflicts_with` and `requires`.
+    pub constraints: Vec<FieldConstraint>,
+    /// Sources the field may be read from, from `#[gonfig(sources = "...")]`
+    /// and `#[gonfig(no_env)]`. `None` allows every source.
+    pub sources: Option<Vec<Source>>,
+    /// Former names of the field, from `#[gonfig(alias = "...")]`. Each one is
+    /// composed into an environment variable, CLI flag and file key like the
+    /// field's own name.
+    pub aliases: Vec<&'static str>,
+    /// Former exact environment variable names, from `#[gonfig(deprecated_env = "...")]`.
+    pub deprecated_env: Vec<&'static str>,
+    /// Former file keys relative to the parent struct, from `#[gonfig(deprecated_key = "...")]`.
+    pub deprecated_keys: Vec<&'static str>,
+    /// Doc comment of the field, used as help text.
+    pub doc: Option<&'static str>,
+    /// Single-character CLI flag from `#[gonfig(short = 'p')]`.
+    pub short: Option<char>,
+    /// Kind of value the field holds, derived from its type.
+    pub kind: ValueKind,
+    /// Whether the value is hidden when the configuration is printed, from
+    /// `#[gonfig(sensitive)]`.
+    pub sensitive: bool,
+    /// Whether the field is an enum selected by a CLI subcommand, from
+    /// `#[gonfig(subcommand)]`. Each variant's settings live in a section
+    /// named after the variant, next to the field.
+    pub subcommand: bool,
+    /// Position of the field among the CLI's positional arguments, from
+    /// `#[gonfig(positional)]`. A list field takes every remaining argument.
+    pub positional: Option<usize>,
+    /// Custom parser of the field's value, from `#[gonfig(parse_with = "...")]`.
+    /// Environment variables and CLI flags pass it the raw string.
+    pub parser: Option<FieldParser>,
+    /// Whether the field is an `Option`. A nested section is then left unset,
+    /// defaults included, unless a source sets one of its fields.
+    pub optional: bool,
+}
+
+/// Turns the raw value of a field, as set in a source, into the value handed
+/// to serde. Errors describe why the value is invalid.
+pub type FieldParser = fn(&Value) -> std::result::Result<Value, String>;
+
+/// Kind of value held by a leaf field, used to parse and describe CLI arguments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueKind {
+    /// `bool`: a flag that may be followed by `true` or `false`.
+    Bool,
+    /// Signed or unsigned integers.
+    Integer,
+    /// `f32` and `f64`.
+    Float,
+    /// `String`, `char` and other text.
+    String,
+    /// `PathBuf`.
+    Path,
+    /// `Vec<T>` and other sequences; the flag can be repeated.
+    List,
+    /// Anything else, parsed like the values of [`Cli::from_vec`](crate::Cli::from_vec).
+    #[default]
+    Any,
+}
+
+impl ValueKind {
+    /// Placeholder shown for the value in help output, e.g. `<INT>`.
+    pub fn value_name(self) -> &'static str {
+        match self {
+            ValueKind::Bool => "BOOL",
+            ValueKind::Integer => "INT",
+            ValueKind::Float => "FLOAT",
+            ValueKind::String => "STRING",
+            ValueKind::Path => "PATH",
+            ValueKind::List | ValueKind::Any => "VALUE",
+        }
+    }
+
+    /// Name of the kind in documentation, e.g. `integer`.
+    pub fn type_name(self) -> &'static str {
+        match self {
+            ValueKind::Bool => "boolean",
+            ValueKind::Integer => "integer",
+            ValueKind::Float => "float",
+            ValueKind::String => "string",
+            ValueKind::Path => "path",
+            ValueKind::List => "list",
+            ValueKind::Any => "any",
+        }
+    }
+}
+
+/// Sources allowed for a field and, if it is a nested struct, everything below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRestriction {
+    /// Dotted path of the field in the merged configuration.
+    pub path: String,
+    /// Sources the value may come from. Defaults are always allowed.
+    pub allowed: Vec<Source>,
+}
+
+/// Composed source names for a leaf field of a (possibly nested) struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMapping {
+    /// Dotted path of the field in the merged configuration, e.g. `database.host`.
+    pub path: String,
+    /// Environment variable read for the field, e.g. `APP_DATABASE_HOST`.
+    pub env_key: String,
+    /// CLI flag (without leading dashes) read for the field, e.g. `database-host`.
+    pub cli_key: String,
+    /// Other environment variables read when `env_key` is unset, e.g.
+    /// `APP_STORAGE__KIND` for the tag of an enum.
+    pub env_aliases: Vec<String>,
+    /// Former names still read when the current ones are absent.
+    pub deprecated: DeprecatedNames,
+    /// Doc comment of the field.
+    pub doc: Option<&'static str>,
+    /// Raw default value of the field.
+    pub default: Option<&'static str>,
+    /// Default value of the field, parsed from `default` or computed by the
+    /// field's `default_fn`.
+    pub default_value: Option<Value>,
+    /// Single-character CLI flag.
+    pub short: Option<char>,
+    /// Kind of value the field holds.
+    pub kind: ValueKind,
+    /// Subcommand whose flags include `cli_key`, for fields of a
+    /// `#[gonfig(subcommand)]` variant.
+    pub subcommand: Option<&'static str>,
+    /// Position of the field among the CLI's positional arguments, which
+    /// replaces the `cli_key` flag.
+    pub positional: Option<usize>,
+    /// Whether the field has a custom parser, so sources keep its value as
+    /// the raw string.
+    pub raw: bool,
+    /// Sources the field may be read from, combining the restrictions of the
+    /// field and of the structs containing it. `None` allows every source.
+    pub sources: Option<Vec<Source>>,
+}
+
+/// Former names of a field, composed through nested structs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeprecatedNames {
+    /// Former environment variables, e.g. `LEGACY_DB_URL`.
+    pub env_keys: Vec<String>,
+    /// Former CLI flags without leading dashes, e.g. `db-url`.
+    pub cli_keys: Vec<String>,
+    /// Former dotted paths in config files, e.g. `database.url`.
+    pub paths: Vec<String>,
+}
+
+impl FieldMapping {
+    /// Whether the field may be read from `source`.
+    // `Option::is_none_or` needs Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn allows(&self, source: Source) -> bool {
+        self.sources.as_ref().map_or(true, |s| s.contains(&source))
+    }
+}
+
+impl DeprecatedNames {
+    /// Whether the field has no former names.
+    pub fn is_empty(&self) -> bool {
+        self.env_keys.is_empty() && self.cli_keys.is_empty() && self.paths.is_empty()
+    }
+}
+
+/// Implemented by `#[derive(Gonfig)]` to expose the struct's [`StructSchema`].
+pub trait GonfigSchema {
+    /// Describe the struct and its configurable fields.
+    fn gonfig_schema() -> StructSchema;
+}
+
+impl StructSchema {
+    /// Create an empty schema for the struct called `name`.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+
+    /// Set the environment variable prefix.
+    pub fn env_prefix(mut self, prefix: &'static str) -> Self {
+        self.env_prefix = Some(prefix).filter(|p| !p.is_empty());
+        self
+    }
+
+    /// Append a field.
+    pub fn field(mut self, field: FieldSchema) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Set the description of the type.
+    pub fn about(mut self, about: &'static str) -> Self {
+        self.about = Some(about);
+        self
+    }
+
+    /// Take the defaults of every field from `default_fn`, which returns an
+    /// object. Field defaults take precedence over it.
+    pub fn default_fn(mut self, default_fn: fn() -> Value) -> Self {
+        self.default_fn = Some(default_fn);
+        self
+    }
+
+    /// Append an enum variant.
+    pub fn variant(mut self, variant: VariantSchema) -> Self {
+        self.variants.push(variant);
+        self
+    }
+
+    /// Set the enum representation.
+    pub fn tagging(mut self, tagging: EnumTagging) -> Self {
+        self.tagging = tagging;
+        self
+    }
+
+    /// Whether this schema describes an enum.
+    pub fn is_enum(&self) -> bool {
+        !self.variants.is_empty()
+    }
+
+    /// Compute the environment and CLI names of every leaf field.
+    ///
+    /// Names are composed from the path of field names: each nesting level
+    /// appends `_{FIELD}` to the environment variable and `-{field}` to the
+    /// CLI flag. An `env_name`/`cli_name` override replaces the composed name
+    /// for that field and, for nested fields, becomes the base of its children.
+    /// The `env_prefix` of a nested struct is ignored; only the prefix of the
+    /// struct being loaded applies.
+    pub fn field_mappings(&self) -> Vec<FieldMapping> {
+        let mut mappings = Vec::new();
+        self.collect_mappings(
+            "",
+            self.env_prefix,
+            None,
+            &DeprecatedNames::default(),
+            &mut mappings,
+        );
+
+        let restrictions = self.source_restrictions();
+        for mapping in &mut mappings {
+            for restriction in &restrictions {
+                let applies = mapping.path == restriction.path
+                    || mapping.path.starts_with(&format!("{}.", restriction.path));
+                if !applies {
+                    continue;
+                }
+                mapping.sources = Some(match mapping.sources.take() {
+                    Some(sources) => sources
+                        .into_iter()
+                        .filter(|s| restriction.allowed.contains(s))
+                        .collect(),
+                    None => restriction.allowed.clone(),
+                });
+            }
+        }
+        mappings
+    }
+
+    /// Compute the environment variable of every nested struct field, as
+    /// `(dotted path, env key)`.
+    ///
+    /// The variable may hold the whole section as a JSON object, such as
+    /// `APP_DATABASE='{"host": "db"}'`; the variables of its leaf fields take
+    /// precedence over it. Sections that may not come from the environment
+    /// are skipped.
+    pub fn section_env_keys(&self) -> Vec<(String, String)> {
+        let mut sections = Vec::new();
+        self.collect_sections("", self.env_prefix, &mut sections);
+        sections
+    }
+
+    fn collect_sections(
+        &self,
+        path: &str,
+        env_base: Option<&str>,
+        sections: &mut Vec<(String, String)>,
+    ) {
+        for field in &self.fields {
+            let Some(nested) = field.nested.as_ref().filter(|n| !n.is_enum()) else {
+                continue;
+            };
+            let env_denied = field
+                .sources
+                .as_ref()
+                .is_some_and(|s| !s.contains(&Source::Environment));
+            if field.subcommand || env_denied {
+                continue;
+            }
+
+            let field_path = join(Some(path).filter(|p| !p.is_empty()), ".", field.name);
+            let env_key = match field.env_name {
+                Some(name) => name.to_string(),
+                None => join(env_base, "_", &field.env_part()),
+            };
+            nested.collect_sections(&field_path, Some(&env_key), sections);
+            sections.push((field_path, env_key));
+        }
+    }
+
+    fn collect_mappings(
+        &self,
+        path: &str,
+        env_base: Option<&str>,
+        cli_base: Option<&str>,
+        deprecated: &DeprecatedNames,
+        mappings: &mut Vec<FieldMapping>,
+    ) {
+        if !self.is_enum() {
+            collect_field_mappings(&self.fields, path, env_base, cli_base, deprecated, mappings);
+            return;
+        }
+
+        let (tag, content) = match self.tagging {
+            EnumTagging::Internal { tag } => (tag, None),
+            EnumTagging::Adjacent { tag, content } => (tag, Some(content)),
+            // The selector is the value itself; variant data comes from files
+            // or a JSON value such as `APP_STORAGE='{"s3": {...}}'`
+            EnumTagging::External | EnumTagging::Untagged => {
+                if !path.is_empty() {
+                    mappings.push(FieldMapping {
+                        path: path.to_string(),
+                        env_key: env_base.unwrap_or_default().to_string(),
+                        cli_key: cli_base.unwrap_or_default().to_string(),
+                        env_aliases: Vec::new(),
+                        deprecated: deprecated.clone(),
+                        doc: self.about,
+                        default: None,
+                        default_value: None,
+                        short: None,
+                        kind: ValueKind::Any,
+                        subcommand: None,
+                        positional: None,
+                        raw: false,
+                        sources: None,
+                    });
+                }
+                return;
+            }
+        };
+
+        let tag_field = [FieldSchema::new(tag).kind(ValueKind::String)];
+        collect_field_mappings(&tag_field, path, env_base, cli_base, deprecated, mappings);
+
+        // The tag may also be set like a key of a section, e.g. `APP_STORAGE__KIND`
+        if let (false, Some(env_base), Some(tag_mapping)) =
+            (path.is_empty(), env_base, mappings.last_mut())
+        {
+            tag_mapping
+                .env_aliases
+                .push(format!("{}__{}", env_base, tag_field[0].env_part()));
+        }
+
+        // Fields of every variant are mapped; serde picks those of the selected one
+        let (path, env_base, cli_base, deprecated) = match content {
+            Some(content) => (
+                join(Some(path).filter(|p| !p.is_empty()), ".", content),
+                Some(join(env_base, "_", &content.to_uppercase())),
+                Some(join(cli_base, "-", &content.replace('_', "-"))),
+                deprecated.child(&FieldSchema::new(content), None, None, ""),
+            ),
+            None => (
+                path.to_string(),
+                env_base.map(str::to_string),
+                cli_base.map(str::to_string),
+                deprecated.clone(),
+            ),
+        };
+
+        let mut variant_mappings = Vec::new();
+        for variant in &self.variants {
+            collect_field_mappings(
+                &variant.fields,
+                &path,
+                env_base.as_deref(),
+                cli_base.as_deref(),
+                &deprecated,
+                &mut variant_mappings,
+            );
+        }
+        for mapping in variant_mappings {
+            if !mappings.iter().any(|m| m.path == mapping.path) {
+                mappings.push(mapping);
+            }
+        }
+    }
+
+    /// Map the fields of each variant of a subcommand enum to the section
+    /// named after the variant, next to the enum field at `path`.
+    ///
+    /// Environment variables separate the section with a double underscore,
+    /// e.g. `APP_SERVE__PORT`; flags are named within the subcommand.
+    fn collect_subcommand_mappings(
+        &self,
+        path: &str,
+        env_base: Option<&str>,
+        mappings: &mut Vec<FieldMapping>,
+    ) {
+        for variant in &self.variants {
+            let section_path = join(Some(path).filter(|p| !p.is_empty()), ".", variant.name);
+            let section_env = format!("{}_", join(env_base, "_", &variant.name.to_uppercase()));
+
+            let mut variant_mappings = Vec::new();
+            collect_field_mappings(
+                &variant.fields,
+                &section_path,
+                Some(&section_env),
+                None,
+                &DeprecatedNames::default(),
+                &mut variant_mappings,
+            );
+            mappings.extend(variant_mappings.into_iter().map(|mapping| FieldMapping {
+                subcommand: Some(variant.name),
+                ..mapping
+            }));
+        }
+    }
+
+    /// The subcommand enum fields of the struct, as `(field name, enum schema)`.
+    pub fn subcommands(&self) -> impl Iterator<Item = (&'static str, &StructSchema)> {
+        self.fields
+            .iter()
+            .filter(|f| f.subcommand)
+            .filter_map(|f| Some((f.name, f.nested.as_ref()?)))
+    }
+
+    /// Move the section of the selected subcommand under its enum field.
+    ///
+    /// The variant is the one named by `selected`, or else the value already
+    /// set for the field, e.g. `command = "serve"` in a file or the field's
+    /// default. Top-level settings that the section leaves unset are copied
+    /// into it when the variant has a field of the same name. The sections of
+    /// the other variants are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] listing the subcommands when the selected one
+    /// is not a variant of the enum.
+    pub fn assemble_subcommands(&self, value: &mut Value, selected: Option<&str>) -> Result<()> {
+        let Value::Object(root) = value else {
+            return Ok(());
+        };
+
+        for (field, commands) in self.subcommands() {
+            let mut sections: Vec<(&'static str, Option<Value>)> = commands
+                .variants
+                .iter()
+                .map(|v| (v.name, root.remove(v.name)))
+                .collect();
+
+            let selector = selected
+                .map(str::to_string)
+                .or_else(|| root.get(field).and_then(Value::as_str).map(str::to_string));
+            let Some(selector) = selector else {
+                continue;
+            };
+            let variant = commands.find_variant(&selector, field)?;
+
+            let mut section = sections
+                .iter_mut()
+                .find(|(name, _)| *name == variant.name)
+                .and_then(|(_, section)| section.take())
+                .filter(Value::is_object)
+                .unwrap_or_else(|| Value::Object(Map::new()));
+            for variant_field in &variant.fields {
+                let shared = root.get(variant_field.name).filter(|v| !v.is_null());
+                if let (Some(shared), Value::Object(section)) = (shared, &mut section) {
+                    section
+                        .entry(variant_field.name)
+                        .or_insert_with(|| shared.clone());
+                }
+            }
+
+            let selected_value = if variant.fields.is_empty() {
+                Value::String(variant.name.to_string())
+            } else {
+                let mut map = Map::new();
+                map.insert(variant.name.to_string(), section);
+                Value::Object(map)
+            };
+            root.insert(field.to_string(), selected_value);
+        }
+        Ok(())
+    }
+
+    /// Build the default values of every field as a nested object.
+    ///
+    /// A default is parsed as JSON when possible and used as a plain string
+    /// otherwise. Nested structs contribute their defaults under the field's
+    /// key, below those the parent's `Default` gives for the field.
+    pub fn defaults(&self) -> Value {
+        // Variant fields only apply once a variant is selected
+        if self.is_enum() {
+            return Value::Object(Map::new());
+        }
+
+        let mut defaults = match self.default_fn.map(|f| f()) {
+            Some(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+
+        for field in &self.fields {
+            // Defaults of subcommand variants go to their sections
+            if let Some(nested) = field.nested.as_ref().filter(|_| field.subcommand) {
+                for variant in &nested.variants {
+                    let section = StructSchema {
+                        fields: variant.fields.clone(),
+                        ..StructSchema::default()
+                    }
+                    .defaults();
+                    if section.as_object().is_some_and(|m| !m.is_empty()) {
+                        defaults.insert(variant.name.to_string(), section);
+                    }
+                }
+            }
+
+            if let Some(value) = field.default_json() {
+                defaults.insert(field.name.to_string(), value);
+            } else if field.optional {
+                // Filled in by `section_defaults` once a source sets the section
+                continue;
+            } else if let Some(nested) = field.nested.as_ref().filter(|_| !field.subcommand) {
+                let mut nested_defaults = nested.defaults();
+                if let Some(parent) = defaults.remove(field.name) {
+                    nested_defaults = MergeStrategy::Deep.merge(nested_defaults, parent);
+                }
+                if nested_defaults.as_object().is_some_and(|m| !m.is_empty()) {
+                    defaults.insert(field.name.to_string(), nested_defaults);
+                }
+            }
+        }
+
+        Value::Object(defaults)
+    }
+
+    /// Fill in the defaults of optional nested sections set by a source,
+    /// which [`defaults`](Self::defaults) leaves out so that an unset section
+    /// stays `None`. Values already set take precedence.
+    pub fn section_defaults(&self, value: &mut Value) {
+        let Value::Object(map) = value else {
+            return;
+        };
+        for field in &self.fields {
+            let Some(nested) = field.nested.as_ref().filter(|_| !field.subcommand) else {
+                continue;
+            };
+            let Some(child) = map.get_mut(field.name).filter(|v| v.is_object()) else {
+                continue;
+            };
+            if field.optional && field.default_json().is_none() {
+                let set = std::mem::take(child);
+                *child = MergeStrategy::Deep.merge(nested.defaults(), set);
+            }
+            nested.section_defaults(child);
+        }
+    }
+
+    /// Canonicalize enum variant selectors in a merged configuration.
+    ///
+    /// Variant names are matched case-insensitively and rewritten to their
+    /// serialized spelling, so `APP_STORAGE_KIND=S3` selects the `s3` variant.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] listing the allowed variants when a selector
+    /// does not name any variant.
+    pub fn normalize(&self, value: &mut Value) -> Result<()> {
+        self.normalize_at(value, "")
+    }
+
+    fn normalize_at(&self, value: &mut Value, path: &str) -> Result<()> {
+        if !self.is_enum() {
+            return normalize_fields(&self.fields, value, path);
+        }
+
+        match self.tagging {
+            EnumTagging::External => match value {
+                Value::String(selector) => {
+                    let variant = self.find_variant(selector, path)?;
+                    *selector = variant.name.to_string();
+                }
+                Value::Object(map) if map.len() == 1 => {
+                    let selector = map.keys().next().cloned().unwrap_or_default();
+                    let variant = self.find_variant(&selector, path)?;
+                    let mut inner = map.remove(&selector).unwrap_or(Value::Null);
+                    let variant_path =
+                        join(Some(path).filter(|p| !p.is_empty()), ".", variant.name);
+                    normalize_fields(&variant.fields, &mut inner, &variant_path)?;
+                    map.insert(variant.name.to_string(), inner);
+                }
+                _ => {}
+            },
+            EnumTagging::Internal { tag } => {
+                let selector = value.get(tag).and_then(Value::as_str).map(str::to_string);
+                if let Some(selector) = selector {
+                    let variant = self.find_variant(&selector, path)?;
+                    value[tag] = Value::String(variant.name.to_string());
+                    normalize_fields(&variant.fields, value, path)?;
+                }
+            }
+            EnumTagging::Adjacent { tag, content } => {
+                let selector = value.get(tag).and_then(Value::as_str).map(str::to_string);
+                if let Some(selector) = selector {
+                    let variant = self.find_variant(&selector, path)?;
+                    value[tag] = Value::String(variant.name.to_string());
+                    if let Some(inner) = value.get_mut(content) {
+                        let content_path = join(Some(path).filter(|p| !p.is_empty()), ".", content);
+                        normalize_fields(&variant.fields, inner, &content_path)?;
+                    }
+                }
+            }
+            EnumTagging::Untagged => {}
+        }
+
+        Ok(())
+    }
+
+    /// Replace the values of fields with a custom parser by the parser's
+    /// output, returning the values that could not be parsed.
+    ///
+    /// Like [`validate`](Self::validate), only the fields of the selected
+    /// variant of an enum are parsed.
+    pub fn parse_fields(&self, value: &mut Value) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        self.parse_at(value, "", &mut errors);
+        errors
+    }
+
+    fn parse_at(&self, value: &mut Value, path: &str, errors: &mut Vec<FieldError>) {
+        if !self.is_enum() {
+            parse_fields(&self.fields, value, path, errors);
+            return;
+        }
+
+        let selected = |selector: Option<&str>| {
+            selector.and_then(|s| self.variants.iter().find(|v| v.name == s))
+        };
+
+        match self.tagging {
+            EnumTagging::External => {
+                if let Some((selector, inner)) =
+                    value.as_object_mut().and_then(|m| m.iter_mut().next())
+                {
+                    if let Some(variant) = selected(Some(selector)) {
+                        let variant_path =
+                            join(Some(path).filter(|p| !p.is_empty()), ".", variant.name);
+                        parse_fields(&variant.fields, inner, &variant_path, errors);
+                    }
+                }
+            }
+            EnumTagging::Internal { tag } => {
+                if let Some(variant) = selected(value.get(tag).and_then(Value::as_str)) {
+                    parse_fields(&variant.fields, value, path, errors);
+                }
+            }
+            EnumTagging::Adjacent { tag, content } => {
+                if let Some(variant) = selected(value.get(tag).and_then(Value::as_str)) {
+                    if let Some(inner) = value.get_mut(content) {
+                        let content_path = join(Some(path).filter(|p| !p.is_empty()), ".", content);
+                        parse_fields(&variant.fields, inner, &content_path, errors);
+                    }
+                }
+            }
+            EnumTagging::Untagged => {}
+        }
+    }
+
+    /// Check the field validators against a merged configuration.
+    ///
+    /// Returns every failure rather than stopping at the first one. Only the
+    /// fields of the selected variant of an enum are checked.
+    pub fn validate(&self, value: &Value) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        self.validate_at(value, "", &mut errors);
+        errors
+    }
+
+    fn validate_at(&self, value: &Value, path: &str, errors: &mut Vec<FieldError>) {
+        if !self.is_enum() {
+            validate_fields(&self.fields, value, path, errors);
+            return;
+        }
+
+        let selected = |selector: Option<&str>| {
+            selector.and_then(|s| self.variants.iter().find(|v| v.name == s))
+        };
+
+        match self.tagging {
+            EnumTagging::External => {
+                if let Some((selector, inner)) = value.as_object().and_then(|m| m.iter().next()) {
+                    if let Some(variant) = selected(Some(selector)) {
+                        let variant_path =
+                            join(Some(path).filter(|p| !p.is_empty()), ".", variant.name);
+                        validate_fields(&variant.fields, inner, &variant_path, errors);
+                    }
+                }
+            }
+            EnumTagging::Internal { tag } => {
+                if let Some(variant) = selected(value.get(tag).and_then(Value::as_str)) {
+                    validate_fields(&variant.fields, value, path, errors);
+                }
+            }
+            EnumTagging::Adjacent { tag, content } => {
+                if let (Some(variant), Some(inner)) = (
+                    selected(value.get(tag).and_then(Value::as_str)),
+                    value.get(content),
+                ) {
+                    let content_path = join(Some(path).filter(|p| !p.is_empty()), ".", content);
+                    validate_fields(&variant.fields, inner, &content_path, errors);
+                }
+            }
+            EnumTagging::Untagged => {}
+        }
+    }
+
+    /// Collect the cross-field constraints of every field, with paths from the root.
+    ///
+    /// Constraints declared inside enum variants are not included.
+    pub fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        self.collect_constraints("", &mut constraints);
+        constraints
+    }
+
+    fn collect_constraints(&self, path: &str, constraints: &mut Vec<Constraint>) {
+        let base = Some(path).filter(|p| !p.is_empty());
+        for field in &self.fields {
+            let field_path = join(base, ".", field.name);
+
+            for constraint in &field.constraints {
+                constraints.push(match *constraint {
+                    FieldConstraint::RequiredIf(condition) => Constraint::RequiredIf {
+                        field: field_path.clone(),
+                        condition: join(base, ".", condition),
+                    },
+                    FieldConstraint::ConflictsWith(other) => Constraint::ConflictsWith {
+                        field: field_path.clone(),
+                        other: join(base, ".", other),
+                    },
+                    FieldConstraint::Requires(other) => Constraint::Requires {
+                        field: field_path.clone(),
+                        other: join(base, ".", other),
+                    },
+                });
+            }
+
+            if let Some(nested) = &field.nested {
+                nested.collect_constraints(&field_path, constraints);
+            }
+        }
+    }
+
+    /// Collect the source restrictions of every field, with paths from the root.
+    ///
+    /// Restrictions declared inside enum variants are not included.
+    pub fn source_restrictions(&self) -> Vec<SourceRestriction> {
+        let mut restrictions = Vec::new();
+        self.collect_restrictions("", &mut restrictions);
+        restrictions
+    }
+
+    fn collect_restrictions(&self, path: &str, restrictions: &mut Vec<SourceRestriction>) {
+        let base = Some(path).filter(|p| !p.is_empty());
+        for field in &self.fields {
+            let field_path = join(base, ".", field.name);
+
+            if let Some(allowed) = &field.sources {
+                restrictions.push(SourceRestriction {
+                    path: field_path.clone(),
+                    allowed: allowed.clone(),
+                });
+            }
+
+            if let Some(nested) = &field.nested {
+                nested.collect_restrictions(&field_path, restrictions);
+            }
+        }
+    }
+
+    /// Replace the values of sensitive fields in `value` with `"<redacted>"`.
+    ///
+    /// `null` values are kept, so that a missing secret is still visible.
+    pub fn redact(&self, value: &mut Value) {
+        if !self.is_enum() {
+            return redact_fields(&self.fields, value);
+        }
+
+        let variant = |selector: Option<&Value>| {
+            let selector = selector.and_then(Value::as_str)?;
+            self.variants
+                .iter()
+                .find(|v| v.name.eq_ignore_ascii_case(selector))
+        };
+        match self.tagging {
+            EnumTagging::External => {
+                if let Value::Object(map) = value {
+                    for (selector, inner) in map.iter_mut() {
+                        if let Some(variant) = variant(Some(&Value::String(selector.clone()))) {
+                            redact_fields(&variant.fields, inner);
+                        }
+                    }
+                }
+            }
+            EnumTagging::Internal { tag } => {
+                if let Some(variant) = variant(value.get(tag)) {
+                    redact_fields(&variant.fields, value);
+                }
+            }
+            EnumTagging::Adjacent { tag, content } => {
+                if let Some(variant) = variant(value.get(tag)) {
+                    if let Some(inner) = value.get_mut(content) {
+                        redact_fields(&variant.fields, inner);
+                    }
+                }
+            }
+            // The variant is unknown, so every field that could be sensitive is hidden
+            EnumTagging::Untagged => {
+                for variant in &self.variants {
+                    redact_fields(&variant.fields, value);
+                }
+            }
+        }
+    }
+
+    /// Remove the keys of `value` that are not fields of the schema, e.g.
+    /// unrelated `APP_*` environment variables or former names of fields.
+    pub fn retain_fields(&self, value: &mut Value) {
+        if !self.is_enum() {
+            return retain_fields(&self.fields, value);
+        }
+
+        let variant = |selector: Option<&Value>| {
+            let selector = selector.and_then(Value::as_str)?;
+            self.variants
+                .iter()
+                .find(|v| v.name.eq_ignore_ascii_case(selector))
+        };
+        match self.tagging {
+            EnumTagging::External => {
+                if let Value::Object(map) = value {
+                    map.retain(|selector, _| {
+                        variant(Some(&Value::String(selector.clone()))).is_some()
+                    });
+                    for (selector, inner) in map.iter_mut() {
+                        if let Some(variant) = variant(Some(&Value::String(selector.clone()))) {
+                            retain_fields(&variant.fields, inner);
+                        }
+                    }
+                }
+            }
+            EnumTagging::Internal { tag } => {
+                if let Some(variant) = variant(value.get(tag)) {
+                    let fields: Vec<FieldSchema> = std::iter::once(FieldSchema::new(tag))
+                        .chain(variant.fields.iter().cloned())
+                        .collect();
+                    retain_fields(&fields, value);
+                }
+            }
+            EnumTagging::Adjacent { tag, content } => {
+                if let Some(variant) = variant(value.get(tag)) {
+                    if let Value::Object(map) = value {
+                        map.retain(|key, _| key == tag || key == content);
+                    }
+                    if let Some(inner) = value.get_mut(content) {
+                        retain_fields(&variant.fields, inner);
+                    }
+                }
+            }
+            // The variant is unknown, so the fields of every variant are kept
+            EnumTagging::Untagged => {
+                let fields: Vec<FieldSchema> = self
+                    .variants
+                    .iter()
+                    .flat_map(|v| v.fields.iter().cloned())
+                    .collect();
+                retain_fields(&fields, value);
+            }
+        }
+    }
+
+    fn find_variant(&self, selector: &str, path: &str) -> Result<&VariantSchema> {
+        self.variants
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(selector))
+            .ok_or_else(|| {
+                let allowed: Vec<&str> = self.variants.iter().map(|v| v.name).collect();
+                Error::Config(format!(
+                    "Invalid variant '{}' for '{}': expected one of {}",
+                    selector,
+                    if path.is_empty() { self.name } else { path },
+                    allowed.join(", ")
+                ))
+            })
+    }
+}
+
+impl VariantSchema {
+    /// Create a unit variant serialized as `name`.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+
+    /// Append a field carried by the variant.
+    pub fn field(mut self, field: FieldSchema) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Take the fields of a newtype variant's inner type when it derives `Gonfig`.
+    pub fn fields_from(mut self, schema: Option<StructSchema>) -> Self {
+        if let Some(schema) = schema {
+            self.fields.extend(schema.fields);
+            self.about = self.about.or(schema.about);
+        }
+        self
+    }
+
+    /// Set the description of the variant.
+    pub fn about(mut self, about: &'static str) -> Self {
+        self.about = Some(about);
+        self
+    }
+}
+
+impl FieldSchema {
+    /// Create a field called `name` with no overrides.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+
+    /// Override the environment variable name.
+    pub fn env_name(mut self, name: &'static str) -> Self {
+        self.env_name = Some(name);
+        self
+    }
+
+    /// Override the CLI flag name.
+    pub fn cli_name(mut self, name: &'static str) -> Self {
+        self.cli_name = Some(name);
+        self
+    }
+
+    /// Set the part of the environment variable contributed by the field,
+    /// e.g. `DB_URL` for a field serialized as `dbUrl`.
+    pub fn env_segment(mut self, segment: &'static str) -> Self {
+        self.env_segment = Some(segment);
+        self
+    }
+
+    /// Set the part of the CLI flag contributed by the field, e.g. `db-url`
+    /// for a field serialized as `dbUrl`.
+    pub fn cli_segment(mut self, segment: &'static str) -> Self {
+        self.cli_segment = Some(segment);
+        self
+    }
+
+    /// The field's part of its environment variables.
+    fn env_part(&self) -> String {
+        match self.env_segment {
+            Some(segment) => segment.to_string(),
+            None => self.name.to_uppercase(),
+        }
+    }
+
+    /// The field's part of its CLI flags.
+    fn cli_part(&self) -> String {
+        match self.cli_segment {
+            Some(segment) => segment.to_string(),
+            None => self.name.replace('_', "-"),
+        }
+    }
+
+    /// Set the raw default value.
+    pub fn default_value(mut self, value: &'static str) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    /// Compute the default with `default_fn`, e.g. from `Default::default()`.
+    pub fn default_fn(mut self, default_fn: fn() -> Value) -> Self {
+        self.default_fn = Some(default_fn);
+        self
+    }
+
+    /// The default value of the field, if it has one.
+    ///
+    /// The raw default is parsed as JSON, or used as a plain string when it
+    /// is not JSON. A `null` from `default_fn` means there is no default.
+    pub fn default_json(&self) -> Option<Value> {
+        match self.default {
+            Some(raw) => Some(
+                raw.parse::<Value>()
+                    .unwrap_or_else(|_| Value::String(raw.to_string())),
+            ),
+            None => self.default_fn.map(|f| f()).filter(|v| !v.is_null()),
+        }
+    }
+
+    /// Add a rule checked against the field's value.
+    pub fn validator(mut self, validator: FieldValidator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Restrict the sources the field may be read from.
+    pub fn sources(mut self, sources: impl IntoIterator<Item = Source>) -> Self {
+        self.sources = Some(sources.into_iter().collect());
+        self
+    }
+
+    /// Set the help text of the field.
+    pub fn doc(mut self, doc: &'static str) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    /// Set the single-character CLI flag.
+    pub fn short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    /// Set the kind of value the field holds.
+    pub fn kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Hide the value when the configuration is printed.
+    pub fn sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+
+    /// Read the field from the positional argument at `index`, counting
+    /// from 0, instead of a flag.
+    pub fn positional(mut self, index: usize) -> Self {
+        self.positional = Some(index);
+        self
+    }
+
+    /// Parse the field's value with `parser` before it is deserialized.
+    pub fn parser(mut self, parser: FieldParser) -> Self {
+        self.parser = Some(parser);
+        self
+    }
+
+    /// Select the field's enum variant with a CLI subcommand.
+    pub fn subcommand(mut self) -> Self {
+        self.subcommand = true;
+        self
+    }
+
+    /// Add a former name of the field.
+    pub fn alias(mut self, name: &'static str) -> Self {
+        self.aliases.push(name);
+        self
+    }
+
+    /// Add a former exact environment variable name.
+    pub fn deprecated_env(mut self, name: &'static str) -> Self {
+        self.deprecated_env.push(name);
+        self
+    }
+
+    /// Add a former file key, relative to the parent struct.
+    pub fn deprecated_key(mut self, key: &'static str) -> Self {
+        self.deprecated_keys.push(key);
+        self
+    }
+
+    /// Add a rule relating the field to a sibling field.
+    pub fn constraint(mut self, constraint: FieldConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Mark the field as an `Option`.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Attach the schema of the field's type when it derives `Gonfig`.
+    pub fn nested(mut self, schema: Option<StructSchema>) -> Self {
+        self.nested = schema;
+        self
+    }
+}
+
+fn collect_field_mappings(
+    fields: &[FieldSchema],
+    path: &str,
+    env_base: Option<&str>,
+    cli_base: Option<&str>,
+    deprecated: &DeprecatedNames,
+    mappings: &mut Vec<FieldMapping>,
+) {
+    for field in fields {
+        let field_path = join(Some(path).filter(|p| !p.is_empty()), ".", field.name);
+
+        let env_key = match field.env_name {
+            Some(name) => name.to_string(),
+            None => join(env_base, "_", &field.env_part()),
+        };
+
+        let cli_key = match field.cli_name {
+            Some(name) => name.to_string(),
+            None => join(cli_base, "-", &field.cli_part()),
+        };
+
+        if let Some(nested) = field.nested.as_ref().filter(|_| field.subcommand) {
+            nested.collect_subcommand_mappings(path, env_base, mappings);
+            continue;
+        }
+
+        let deprecated = deprecated.child(field, env_base, cli_base, path);
+
+        match &field.nested {
+            Some(nested) => nested.collect_mappings(
+                &field_path,
+                Some(&env_key),
+                Some(&cli_key),
+                &deprecated,
+                mappings,
+            ),
+            None => mappings.push(FieldMapping {
+                path: field_path,
+                env_key,
+                cli_key,
+                env_aliases: Vec::new(),
+                deprecated,
+                doc: field.doc,
+                default: field.default,
+                default_value: field.default_json(),
+                short: field.short,
+                kind: field.kind,
+                subcommand: None,
+                positional: field.positional,
+                raw: field.parser.is_some(),
+                sources: None,
+            }),
+        }
+    }
+}
+
+impl DeprecatedNames {
+    /// Former names of `field`: the parent's former names extended with the
+    /// field's name, plus the field's own aliases composed from the current
+    /// `env_base`, `cli_base` and `path`.
+    fn child(
+        &self,
+        field: &FieldSchema,
+        env_base: Option<&str>,
+        cli_base: Option<&str>,
+        path: &str,
+    ) -> DeprecatedNames {
+        let path = Some(path).filter(|p| !p.is_empty());
+        let aliases = field.aliases.iter().map(|a| a.to_lowercase());
+
+        // An exact env_name/cli_name does not depend on the parent's name
+        let inherited_env = match field.env_name {
+            Some(_) => Vec::new(),
+            None => self.env_keys.clone(),
+        };
+        let inherited_cli = match field.cli_name {
+            Some(_) => Vec::new(),
+            None => self.cli_keys.clone(),
+        };
+
+        DeprecatedNames {
+            env_keys: inherited_env
+                .iter()
+                .map(|base| join(Some(base), "_", &field.env_part()))
+                .chain(
+                    aliases
+                        .clone()
+                        .map(|a| join(env_base, "_", &a.to_uppercase())),
+                )
+                .chain(field.deprecated_env.iter().map(|e| e.to_string()))
+                .collect(),
+            cli_keys: inherited_cli
+                .iter()
+                .map(|base| join(Some(base), "-", &field.cli_part()))
+                .chain(
+                    aliases
+                        .clone()
+                        .map(|a| join(cli_base, "-", &a.replace('_', "-"))),
+                )
+                .collect(),
+            paths: self
+                .paths
+                .iter()
+                .map(|base| join(Some(base), ".", field.name))
+                .chain(aliases.map(|a| join(path, ".", &a)))
+                .chain(field.deprecated_keys.iter().map(|k| join(path, ".", k)))
+                .collect(),
+        }
+    }
+}
+
+fn retain_fields(fields: &[FieldSchema], value: &mut Value) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    map.retain(|key, _| fields.iter().any(|f| f.name == key));
+    for field in fields {
+        if let (Some(nested), Some(child)) = (&field.nested, map.get_mut(field.name)) {
+            nested.retain_fields(child);
+        }
+    }
+}
+
+fn redact_fields(fields: &[FieldSchema], value: &mut Value) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for field in fields {
+        let Some(child) = map.get_mut(field.name) else {
+            continue;
+        };
+        if field.sensitive && !child.is_null() {
+            *child = Value::String(REDACTED.to_string());
+        } else if let Some(nested) = &field.nested {
+            nested.redact(child);
+        }
+    }
+}
+
+fn normalize_fields(fields: &[FieldSchema], value: &mut Value, path: &str) -> Result<()> {
+    if let Value::Object(map) = value {
+        for field in fields {
+            if let (Some(nested), Some(child)) = (&field.nested, map.get_mut(field.name)) {
+                let field_path = join(Some(path).filter(|p| !p.is_empty()), ".", field.name);
+                nested.normalize_at(child, &field_path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_fields(
+    fields: &[FieldSchema],
+    value: &mut Value,
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    for field in fields {
+        let Some(field_value) = value.get_mut(field.name).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        let field_path = join(Some(path).filter(|p| !p.is_empty()), ".", field.name);
+
+        match (field.parser, &field.nested) {
+            (Some(parser), _) => match parser(field_value) {
+                Ok(parsed) => *field_value = parsed,
+                Err(message) => errors.push(FieldError {
+                    path: field_path,
+                    message,
+                    origin: None,
+                }),
+            },
+            (None, Some(nested)) => nested.parse_at(field_value, &field_path, errors),
+            (None, None) => {}
+        }
+    }
+}
+
+fn validate_fields(
+    fields: &[FieldSchema],
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    for field in fields {
+        let Some(field_value) = value.get(field.name).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        let field_path = join(Some(path).filter(|p| !p.is_empty()), ".", field.name);
+
+        for validator in &field.validators {
+            if let Err(message) = validator.check(field_value) {
+                errors.push(FieldError {
+                    path: field_path.clone(),
+                    message,
+                    origin: None,
+                });
+            }
+        }
+
+        if let Some(nested) = &field.nested {
+            nested.validate_at(field_value, &field_path, errors);
+        }
+    }
+}
+
+fn join(base: Option<&str>, separator: &str, segment: &str) -> String {
+    match base {
+        Some(base) => format!("{}{}{}", base, separator, segment),
+        None => segment.to_string(),
+    }
+}
+
+/// Support code for the derive macro; not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use super::{GonfigSchema, StructSchema};
+    use serde::de::DeserializeOwned;
+    use std::marker::PhantomData;
+
+    pub use once_cell::sync::Lazy;
+    pub use serde_json::Value;
+
+    /// Serialize a default value, `null` when it cannot be.
+    pub fn default_value<T: serde::Serialize>(value: T) -> Value {
+        serde_json::to_value(value).unwrap_or(Value::Null)
+    }
+
+    /// What a `parse_with` function can take: `&str`, the raw string from an
+    /// environment variable or CLI flag, or `&Value`, any value.
+    pub trait ParseInput<'a>: Sized {
+        fn from_value(value: &'a Value) -> Option<Self>;
+    }
+
+    impl<'a> ParseInput<'a> for &'a str {
+        fn from_value(value: &'a Value) -> Option<Self> {
+            value.as_str()
+        }
+    }
+
+    impl<'a> ParseInput<'a> for &'a Value {
+        fn from_value(value: &'a Value) -> Option<Self> {
+            Some(value)
+        }
+    }
+
+    /// Run a `parse_with` function on a field value, serializing its output.
+    pub fn parse_with<'a, I, T, E, F>(value: &'a Value, parse: F) -> Result<Value, String>
+    where
+        I: ParseInput<'a>,
+        T: serde::Serialize,
+        E: std::fmt::Display,
+        F: FnOnce(I) -> Result<T, E>,
+    {
+        let input =
+            I::from_value(value).ok_or_else(|| format!("expected a string, found {}", value))?;
+        let parsed = parse(input).map_err(|e| e.to_string())?;
+        serde_json::to_value(parsed).map_err(|e| e.to_string())
+    }
+
+    /// Deserialize a field value for a `custom` validator.
+    pub fn typed_field<T: DeserializeOwned>(value: &Value) -> Result<T, String> {
+        serde_json::from_value(value.clone()).map_err(|e| e.to_string())
+    }
+
+    /// Resolves to the nested schema of `T` when `T: GonfigSchema` and to
+    /// `None` otherwise, using autoref-based method resolution:
+    /// `(&&SchemaProbe::<T>::new()).nested_schema()`.
+    pub struct SchemaProbe<T>(PhantomData<T>);
+
+    impl<T> SchemaProbe<T> {
+        pub fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    impl<T> Default for SchemaProbe<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    pub trait ViaSchema {
+        fn nested_schema(&self) -> Option<StructSchema>;
+    }
+
+    impl<T: GonfigSchema> ViaSchema for &SchemaProbe<T> {
+        fn nested_schema(&self) -> Option<StructSchema> {
+            Some(T::gonfig_schema())
+        }
+    }
+
+    pub trait ViaPlain {
+        fn nested_schema(&self) -> Option<StructSchema> {
+            None
+        }
+    }
+
+    impl<T> ViaPlain for SchemaProbe<T> {}
+
+    /// Implemented by `#[derive(Gonfig)]` on externally tagged enums, listing
+    /// the serialized names of the unit variants a string can select.
+    pub trait EnumVariants {
+        const VARIANTS: &'static [&'static str];
+    }
+
+    /// Resolves to the variants of `T` when `T: EnumVariants` and to `None`
+    /// otherwise, as an inherent constant shadowing [`ViaPlainVariants`]:
+    /// `SchemaProbe::<T>::VARIANTS`.
+    impl<T: EnumVariants> SchemaProbe<T> {
+        pub const VARIANTS: Option<&'static [&'static str]> = Some(T::VARIANTS);
+    }
+
+    pub trait ViaPlainVariants {
+        const VARIANTS: Option<&'static [&'static str]> = None;
+    }
+
+    impl<T> ViaPlainVariants for SchemaProbe<T> {}
+
+    /// Whether `name` selects one of `variants`, ignoring ASCII case as the
+    /// builder does; any name is accepted when the variants are unknown.
+    pub const fn is_variant(variants: Option<&[&str]>, name: &str) -> bool {
+        let Some(variants) = variants else {
+            return true;
+        };
+        let name = name.as_bytes();
+        let mut i = 0;
+        while i < variants.len() {
+            let variant = variants[i].as_bytes();
+            let mut j = 0;
+            while j < name.len()
+                && variant.len() == name.len()
+                && variant[j].eq_ignore_ascii_case(&name[j])
+            {
+                j += 1;
+            }
+            if j == name.len() && variant.len() == name.len() {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+}
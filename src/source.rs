@@ -1,5 +1,6 @@
 use crate::error::Result;
 use std::any::Any;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Source {
@@ -20,6 +21,18 @@ impl Source {
     }
 }
 
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Source::Environment => "environment",
+            Source::ConfigFile => "config file",
+            Source::Cli => "CLI",
+            Source::Default => "default value",
+        };
+        f.write_str(name)
+    }
+}
+
 pub trait ConfigSource: Any + Send + Sync {
     fn source_type(&self) -> Source;
 
@@ -30,6 +43,13 @@ pub trait ConfigSource: Any + Send + Sync {
     fn get_value(&self, key: &str) -> Option<serde_json::Value>;
 
     fn as_any(&self) -> &dyn Any;
+
+    /// Describe where the value at a dotted `path` of [`collect`](Self::collect)
+    /// comes from, e.g. `env var APP_PORT`, for use in error messages.
+    fn origin(&self, path: &str) -> Option<String> {
+        let _ = path;
+        None
+    }
 }
 
 pub trait FromSource: Sized {
@@ -0,0 +1,374 @@
+//! Declarative field validation used by `#[gonfig(...)]` validation attributes,
+//! and the [`Diagnostics`] collected by builder validators.
+
+use crate::error::Error;
+use serde_json::{Number, Value};
+use std::fmt;
+
+pub use regex::Regex;
+
+/// A rule checked against a field's value after the configuration is merged
+/// and deserialized.
+///
+/// Missing and `null` values are not checked; whether a field is required is
+/// decided by its type.
+///
+/// # Examples
+///
+/// ```rust
+/// use gonfig::validation::FieldValidator;
+/// use serde_json::json;
+///
+/// let port = FieldValidator::Range { min: Some(1.0), max: Some(65535.0) };
+/// assert!(port.check(&json!(8080)).is_ok());
+/// assert!(port.check(&json!(70000)).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub enum FieldValidator {
+    /// `#[gonfig(range(min = 1, max = 65535))]` - numeric bounds, inclusive.
+    Range { min: Option<f64>, max: Option<f64> },
+    /// `#[gonfig(length(min = 1, max = 64))]` - character count of strings, or
+    /// number of elements of arrays and maps, inclusive.
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// `#[gonfig(regex = "^[a-z]+$")]` - the string must match the pattern.
+    /// The derive compiles each field's pattern once.
+    Regex(Regex),
+    /// `#[gonfig(one_of = ["debug", "info"])]` - the value must be one of the listed values.
+    OneOf(Vec<Value>),
+    /// `#[gonfig(url)]` - the string must be an absolute URL such as `https://host/path`.
+    Url,
+    /// `#[gonfig(non_empty)]` - strings, arrays and maps must not be empty.
+    NonEmpty,
+    /// `#[gonfig(custom = "path::to::fn")]` - a user function over the typed field value.
+    Custom(fn(&Value) -> Result<(), String>),
+}
+
+/// A rule relating a field to another field of the merged configuration.
+///
+/// Paths are dotted paths from the root, e.g. `server.tls_cert`. A field is
+/// *set* when it is present and not `null`. Values that only come from
+/// defaults never cause a conflict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// `field` must be set when `condition` holds. The condition is either a
+    /// path, which holds when that field is set and not `false`, or
+    /// `path=value`, which holds when that field equals `value`.
+    RequiredIf { field: String, condition: String },
+    /// `field` and `other` must not both be set.
+    ConflictsWith { field: String, other: String },
+    /// When `field` is set, `other` must be set as well.
+    Requires { field: String, other: String },
+}
+
+/// Per-field form of a [`Constraint`], with the other field's path relative
+/// to the struct declaring it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldConstraint {
+    /// `#[gonfig(required_if = "enable_tls")]` or `#[gonfig(required_if = "mode=tls")]`
+    RequiredIf(&'static str),
+    /// `#[gonfig(conflicts_with = "port")]`
+    ConflictsWith(&'static str),
+    /// `#[gonfig(requires = "tls_key")]`
+    Requires(&'static str),
+}
+
+/// Where a value of the merged configuration came from.
+pub(crate) struct ValueOrigin {
+    /// Description of the source, e.g. `env var APP_PORT`.
+    pub description: String,
+    /// Whether the value only comes from defaults.
+    pub is_default: bool,
+}
+
+impl Constraint {
+    /// Check the constraint, returning a description of the violation.
+    ///
+    /// `origin` describes the source that set the value at a path.
+    pub(crate) fn check(
+        &self,
+        config: &Value,
+        origin: &dyn Fn(&str) -> Option<ValueOrigin>,
+    ) -> Result<(), String> {
+        let is_set = |path: &str| crate::path::get(config, path).is_some_and(|v| !v.is_null());
+        let set_by = |path: &str| {
+            origin(path)
+                .map(|o| format!("{} set by {}", path, o.description))
+                .unwrap_or_else(|| format!("{} is set", path))
+        };
+
+        match self {
+            Constraint::RequiredIf { field, condition } => {
+                let (condition_path, expected) = match condition.split_once('=') {
+                    Some((path, expected)) => (path.trim(), Some(expected.trim())),
+                    None => (condition.as_str(), None),
+                };
+                let holds = match (crate::path::get(config, condition_path), expected) {
+                    (Some(value), Some(expected)) => {
+                        value.as_str() == Some(expected)
+                            || serde_json::from_str::<Value>(expected)
+                                .is_ok_and(|e| values_match(&e, value))
+                    }
+                    (Some(value), None) => !value.is_null() && *value != Value::Bool(false),
+                    (None, _) => false,
+                };
+
+                if holds && !is_set(field) {
+                    let when = match expected {
+                        Some(expected) => format!("{} is {}", condition_path, expected),
+                        None => format!("{} is set", condition_path),
+                    };
+                    return Err(format!(
+                        "{} is required when {} ({})",
+                        field,
+                        when,
+                        set_by(condition_path)
+                    ));
+                }
+                Ok(())
+            }
+            Constraint::ConflictsWith { field, other } => {
+                // `Option::is_none_or` needs Rust 1.82
+                #[allow(clippy::unnecessary_map_or)]
+                let explicit =
+                    |path: &str| is_set(path) && origin(path).map_or(true, |o| !o.is_default);
+                if explicit(field) && explicit(other) {
+                    return Err(format!(
+                        "{} conflicts with {} ({}, {})",
+                        field,
+                        other,
+                        set_by(field),
+                        set_by(other)
+                    ));
+                }
+                Ok(())
+            }
+            Constraint::Requires { field, other } => {
+                if is_set(field) && !is_set(other) {
+                    return Err(format!(
+                        "{} requires {} to be set ({})",
+                        field,
+                        other,
+                        set_by(field)
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A validation failure for a single field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Dotted path of the field, e.g. `database.port`.
+    pub path: String,
+    /// Description of the failed rule.
+    pub message: String,
+    /// The source that provided the value, e.g. `env var APP_DATABASE_PORT`.
+    pub origin: Option<String>,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)?;
+        if let Some(origin) = &self.origin {
+            write!(f, " (set by {})", origin)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors and non-fatal warnings reported by validators.
+///
+/// Passed to validators registered with
+/// [`ConfigBuilder::diagnose_typed`](crate::ConfigBuilder::diagnose_typed).
+/// Any error fails the build; warnings are returned by
+/// [`ConfigBuilder::build_with_warnings`](crate::ConfigBuilder::build_with_warnings)
+/// or logged through `tracing` by [`ConfigBuilder::build`](crate::ConfigBuilder::build).
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    errors: Vec<Error>,
+    warnings: Vec<String>,
+}
+
+impl Diagnostics {
+    /// Record a validation error.
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(Error::Validation(message.into()));
+    }
+
+    /// Record a non-fatal warning.
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    /// Whether any error has been recorded.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Warnings recorded so far.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub(crate) fn push_error(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    /// Fail with the recorded errors, if any.
+    ///
+    /// A single error is returned as is; several are combined into one
+    /// [`Error::Validation`] listing every message.
+    pub(crate) fn check(&mut self) -> crate::Result<()> {
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.remove(0)),
+            _ => {
+                let messages: Vec<String> = self
+                    .errors
+                    .drain(..)
+                    .map(|e| match e {
+                        Error::Validation(msg) => msg,
+                        other => other.to_string(),
+                    })
+                    .collect();
+                Err(Error::Validation(messages.join("; ")))
+            }
+        }
+    }
+
+    pub(crate) fn into_warnings(self) -> Vec<String> {
+        self.warnings
+    }
+}
+
+impl FieldValidator {
+    /// Check `value` against the rule, returning a description of the failure.
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        match self {
+            FieldValidator::Range { min, max } => {
+                let n = value
+                    .as_f64()
+                    .ok_or_else(|| format!("expected a number, got {}", value))?;
+                if min.is_some_and(|min| n < min) || max.is_some_and(|max| n > max) {
+                    return Err(format!("must be {} (got {})", bounds(min, max), value));
+                }
+                Ok(())
+            }
+            FieldValidator::Length { min, max } => {
+                let len = match value {
+                    Value::String(s) => s.chars().count(),
+                    Value::Array(a) => a.len(),
+                    Value::Object(o) => o.len(),
+                    _ => return Err(format!("expected a string or a list, got {}", value)),
+                };
+                if min.is_some_and(|min| len < min) || max.is_some_and(|max| len > max) {
+                    return Err(format!(
+                        "length must be {} (got {})",
+                        bounds(&min.map(|m| m as f64), &max.map(|m| m as f64)),
+                        len
+                    ));
+                }
+                Ok(())
+            }
+            FieldValidator::Regex(regex) => {
+                let s = expect_str(value)?;
+                if !regex.is_match(s) {
+                    return Err(format!(
+                        "must match pattern '{}' (got '{}')",
+                        regex.as_str(),
+                        s
+                    ));
+                }
+                Ok(())
+            }
+            FieldValidator::OneOf(allowed) => {
+                if allowed.iter().any(|a| values_match(a, value)) {
+                    return Ok(());
+                }
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                Err(format!(
+                    "must be one of {} (got {})",
+                    allowed.join(", "),
+                    value
+                ))
+            }
+            FieldValidator::Url => {
+                let s = expect_str(value)?;
+                if !is_url(s) {
+                    return Err(format!("must be an absolute URL (got '{}')", s));
+                }
+                Ok(())
+            }
+            FieldValidator::NonEmpty => {
+                let empty = match value {
+                    Value::String(s) => s.is_empty(),
+                    Value::Array(a) => a.is_empty(),
+                    Value::Object(o) => o.is_empty(),
+                    _ => false,
+                };
+                if empty {
+                    return Err("must not be empty".to_string());
+                }
+                Ok(())
+            }
+            FieldValidator::Custom(validate) => validate(value),
+        }
+    }
+}
+
+fn bounds(min: &Option<f64>, max: &Option<f64>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("between {} and {}", min, max),
+        (Some(min), None) => format!("at least {}", min),
+        (None, Some(max)) => format!("at most {}", max),
+        (None, None) => "unbounded".to_string(),
+    }
+}
+
+fn expect_str(value: &Value) -> Result<&str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("expected a string, got {}", value))
+}
+
+fn values_match(allowed: &Value, value: &Value) -> bool {
+    match (allowed, value) {
+        (Value::Number(a), Value::Number(b)) => numbers_match(a, b),
+        _ => allowed == value,
+    }
+}
+
+/// Whether two numbers are equal, comparing integers exactly and only
+/// floats as `f64`, so that `5` matches `5.0`.
+fn numbers_match(a: &Number, b: &Number) -> bool {
+    if a.is_f64() || b.is_f64() {
+        return a.as_f64() == b.as_f64();
+    }
+    match (a.as_i64(), b.as_i64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.as_u64().is_some() && a.as_u64() == b.as_u64(),
+    }
+}
+
+fn is_url(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once("://") else {
+        return false;
+    };
+
+    let valid_scheme = scheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    valid_scheme && !host.is_empty() && !host.contains(char::is_whitespace)
+}
@@ -154,3 +154,115 @@ debug = false
     env::remove_var("PRIO_DEBUG");
     Ok(())
 }
+
+#[test]
+fn test_builder_runs_all_validators() {
+    let result: Result<AppConfig, _> = ConfigBuilder::new()
+        .with_defaults(serde_json::json!({
+            "database_url": "mysql://localhost",
+            "port": 0
+        }))
+        .unwrap()
+        .validate_with(|value| {
+            if value["port"] == 0 {
+                return Err(Error::Validation("port must not be 0".into()));
+            }
+            Ok(())
+        })
+        .validate_with(|value| {
+            if !value["database_url"]
+                .as_str()
+                .unwrap_or_default()
+                .starts_with("postgres://")
+            {
+                return Err(Error::Validation("only postgres is supported".into()));
+            }
+            Ok(())
+        })
+        .build();
+
+    match result {
+        Err(Error::Validation(msg)) => {
+            assert!(msg.contains("port must not be 0"), "{}", msg);
+            assert!(msg.contains("only postgres is supported"), "{}", msg);
+        }
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_builder_typed_validation() {
+    let builder = || {
+        ConfigBuilder::new()
+            .with_defaults(serde_json::json!({
+                "database_url": "postgres://localhost",
+                "port": 80,
+                "debug": true
+            }))
+            .unwrap()
+            .validate_typed(|config: &AppConfig| {
+                if config.port < 1024 && !config.debug {
+                    return Err(Error::Validation("privileged port".into()));
+                }
+                Ok(())
+            })
+    };
+
+    let config: AppConfig = builder().build().unwrap();
+    assert_eq!(config.port, 80);
+
+    let result: Result<AppConfig, _> = builder()
+        .validate_typed(|config: &AppConfig| {
+            if config.debug {
+                return Err(Error::Validation("debug is not allowed".into()));
+            }
+            Ok(())
+        })
+        .build();
+    assert!(matches!(result, Err(Error::Validation(msg)) if msg == "debug is not allowed"));
+}
+
+#[test]
+fn test_builder_warnings() {
+    let (config, warnings) = ConfigBuilder::new()
+        .with_defaults(serde_json::json!({
+            "database_url": "postgres://localhost",
+            "port": 8080,
+            "debug": true
+        }))
+        .unwrap()
+        .diagnose_typed(|config: &AppConfig, diagnostics| {
+            if config.debug {
+                diagnostics.warn("debug mode is enabled");
+            }
+            Ok(())
+        })
+        .diagnose_typed(|config: &AppConfig, diagnostics| {
+            if config.port == 8080 {
+                diagnostics.warn("using the default port");
+            }
+            Ok(())
+        })
+        .build_with_warnings::<AppConfig>()
+        .unwrap();
+
+    assert_eq!(config.port, 8080);
+    assert_eq!(
+        warnings,
+        vec!["debug mode is enabled", "using the default port"]
+    );
+
+    let result = ConfigBuilder::new()
+        .with_defaults(serde_json::json!({
+            "database_url": "postgres://localhost",
+            "port": 8080
+        }))
+        .unwrap()
+        .diagnose_typed(|_: &AppConfig, diagnostics| {
+            diagnostics.warn("just a warning");
+            diagnostics.error("a real problem");
+            Ok(())
+        })
+        .build_with_warnings::<AppConfig>();
+    assert!(matches!(result, Err(Error::Validation(msg)) if msg == "a real problem"));
+}
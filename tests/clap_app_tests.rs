@@ -0,0 +1,109 @@
+use clap::{Args, Parser};
+use gonfig::{Cli, ConfigBuilder, ConfigFormat, Error};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+#[derive(Parser, Serialize)]
+struct CliArgs {
+    #[arg(long, default_value = "8080")]
+    port: u16,
+
+    #[arg(long)]
+    host: Option<String>,
+
+    #[arg(long)]
+    verbose: bool,
+
+    #[command(flatten)]
+    database: DatabaseArgs,
+}
+
+#[derive(Args, Serialize)]
+struct DatabaseArgs {
+    #[arg(long, default_value = "5")]
+    pool_size: u32,
+
+    #[arg(long)]
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+    port: u16,
+    host: String,
+    verbose: bool,
+    database: Database,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Database {
+    pool_size: u32,
+    url: String,
+}
+
+fn build(file: &NamedTempFile, args: &[&str]) -> gonfig::Result<Config> {
+    let cli = Cli::with_clap_app_from::<CliArgs, _, _>(args)?;
+    ConfigBuilder::new()
+        .with_file_format(file.path(), ConfigFormat::Toml)?
+        .with_cli_custom(cli)
+        .build()
+}
+
+#[test]
+fn test_clap_defaults_do_not_override_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = NamedTempFile::new()?;
+    writeln!(
+        file,
+        "port = 9000\nhost = \"file-host\"\n[database]\nurl = \"postgres://file\""
+    )?;
+
+    let config = build(&file, &["app"])?;
+    assert_eq!(
+        config,
+        Config {
+            port: 9000,
+            host: "file-host".into(),
+            verbose: false,
+            database: Database {
+                pool_size: 5,
+                url: "postgres://file".into()
+            }
+        }
+    );
+
+    let config = build(
+        &file,
+        &[
+            "app",
+            "--port",
+            "7000",
+            "--url",
+            "postgres://cli",
+            "--verbose",
+        ],
+    )?;
+    assert_eq!(config.port, 7000);
+    assert_eq!(config.host, "file-host");
+    assert!(config.verbose);
+    assert_eq!(config.database.url, "postgres://cli");
+    assert_eq!(config.database.pool_size, 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_clap_values_are_split_by_source() {
+    let cli = Cli::with_clap_app_from::<CliArgs, _, _>(["app", "--pool-size", "10"]).unwrap();
+
+    assert_eq!(cli.get_matches().len(), 1);
+    assert_eq!(cli.get_matches()["database"]["pool_size"], 10);
+    assert!(cli.clap_defaults().get("host").is_none());
+    assert_eq!(cli.clap_defaults()["port"], 8080);
+    assert_eq!(cli.clap_defaults()["verbose"], false);
+
+    match Cli::with_clap_app_from::<CliArgs, _, _>(["app", "--port", "http"]) {
+        Err(Error::Cli(msg)) => assert!(msg.contains("--port"), "{}", msg),
+        other => panic!("expected CLI error, got {:?}", other),
+    }
+}
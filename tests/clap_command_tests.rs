@@ -0,0 +1,148 @@
+use gonfig::{Cli, ConfigBuilder, Error, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Example service
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(
+    allow_cli,
+    env_prefix = "CLAPC",
+    config_file_env = "CLAPC_CONFIG",
+    config_file_cli = "config"
+)]
+struct ServiceConfig {
+    /// Port to listen on
+    #[gonfig(short = 'p', default = "8080")]
+    port: u16,
+
+    /// Log level
+    ///
+    /// One of error, warn, info or debug.
+    #[gonfig(default = "\"info\"")]
+    log_level: String,
+
+    /// Enable verbose output
+    #[gonfig(default = "false")]
+    verbose: bool,
+
+    ratio: Option<f64>,
+
+    #[gonfig(cli_name = "tag")]
+    tags: Vec<String>,
+
+    database: Database,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Database {
+    /// Connection URL
+    #[gonfig(default = "\"postgres://localhost\"")]
+    url: String,
+}
+
+#[test]
+fn test_help_output() {
+    let help = ServiceConfig::gonfig_command().render_help().to_string();
+
+    assert!(help.contains("Example service"), "{}", help);
+    assert!(help.contains("-p, --port <INT>"), "{}", help);
+    assert!(
+        help.contains("Port to listen on [env: CLAPC_PORT] [default: 8080]"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("Log level [env: CLAPC_LOG_LEVEL] [default: info]"),
+        "{}",
+        help
+    );
+    assert!(help.contains("--ratio <FLOAT>"), "{}", help);
+    assert!(help.contains("--database-url <STRING>"), "{}", help);
+    assert!(help.contains("--config <PATH>"), "{}", help);
+    assert!(help.contains("[env: CLAPC_CONFIG]"), "{}", help);
+}
+
+#[test]
+fn test_matches_override_lower_layers() {
+    env::set_var("CLAPC_PORT", "9000");
+    env::set_var("CLAPC_LOG_LEVEL", "warn");
+
+    let config = ServiceConfig::from_gonfig_with_args(
+        ConfigBuilder::new(),
+        [
+            "app",
+            "-p",
+            "7000",
+            "--verbose",
+            "--ratio",
+            "0.5",
+            "--tag",
+            "a",
+            "--tag",
+            "b",
+            "--database-url",
+            "postgres://db",
+        ],
+    );
+
+    env::remove_var("CLAPC_PORT");
+    env::remove_var("CLAPC_LOG_LEVEL");
+
+    assert_eq!(
+        config.unwrap(),
+        ServiceConfig {
+            port: 7000,
+            log_level: "warn".into(),
+            verbose: true,
+            ratio: Some(0.5),
+            tags: vec!["a".into(), "b".into()],
+            database: Database {
+                url: "postgres://db".into()
+            },
+        }
+    );
+}
+
+#[test]
+fn test_invalid_arguments_are_errors() {
+    for args in [
+        vec!["app", "--prot", "80"],
+        vec!["app", "--port", "eighty"],
+        vec!["app", "--tag"],
+    ] {
+        match ServiceConfig::from_gonfig_with_args(ConfigBuilder::new(), args.clone()) {
+            Err(Error::Cli(_)) => {}
+            other => panic!("expected CLI error for {:?}, got {:?}", args, other),
+        }
+    }
+}
+
+#[test]
+fn test_lenient_arguments_read_fields_and_ignore_the_rest() {
+    let args = [
+        "app",
+        "--nocapture",
+        "-p",
+        "7000",
+        "--test-threads",
+        "2",
+        "--tag",
+        "a",
+        "--database-url",
+        "postgres://db",
+    ];
+    let schema = ServiceConfig::gonfig_schema();
+    let cli = Cli::from_vec(args.map(String::from).to_vec()).with_schema(&schema);
+
+    let config: ServiceConfig = ConfigBuilder::new()
+        .with_defaults(schema.defaults())
+        .unwrap()
+        .with_cli_custom(cli)
+        .with_schema(schema)
+        .build()
+        .unwrap();
+
+    assert_eq!(config.port, 7000);
+    assert_eq!(config.tags, vec!["a".to_string()]);
+    assert_eq!(config.database.url, "postgres://db");
+}
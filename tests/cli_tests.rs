@@ -108,3 +108,206 @@ fn test_cli_safe_float_parsing() {
         Some("NaN")
     );
 }
+
+fn parse(args: &[&str]) -> serde_json::Value {
+    let args = std::iter::once("program")
+        .chain(args.iter().copied())
+        .map(String::from)
+        .collect();
+    Cli::from_vec(args).collect().unwrap()
+}
+
+#[test]
+fn test_cli_equals_and_negative_values() {
+    let result = parse(&[
+        "--port=8080",
+        "--offset",
+        "-5",
+        "--scale",
+        "-0.5",
+        "-n=3",
+        "-j4",
+    ]);
+
+    assert_eq!(result["port"], 8080);
+    assert_eq!(result["offset"], -5);
+    assert_eq!(result["scale"], -0.5);
+    assert_eq!(result["n"], 3);
+    assert_eq!(result["j"], 4);
+
+    // `=` splits once, and `-` alone is a value
+    let result = parse(&["--filter=a=b", "--input", "-"]);
+    assert_eq!(result["filter"], "a=b");
+    assert_eq!(result["input"], "-");
+}
+
+#[test]
+fn test_cli_negation_and_flag_clusters() {
+    let result = parse(&["--no-color", "-vq", "--debug", "--no-debug"]);
+
+    assert_eq!(result["color"], false);
+    assert_eq!(result["v"], true);
+    assert_eq!(result["q"], true);
+    assert_eq!(result["debug"], false);
+
+    let result = parse(&["-vvv", "--verbose", "--verbose"]);
+    assert_eq!(result["v"], 3);
+    assert_eq!(result["verbose"], 2);
+}
+
+#[test]
+fn test_cli_only_finite_numbers_are_values() {
+    // `inf` and `nan` parse as floats, but here they are short flags
+    let result = parse(&["-xinf"]);
+    for flag in ["x", "i", "n", "f"] {
+        assert_eq!(result[flag], true, "-{}", flag);
+    }
+
+    let result = parse(&["--limit", "-inf", "--offset", "-1e3"]);
+    assert_eq!(result["limit"], true);
+    assert_eq!(result["i"], true);
+    assert_eq!(result["offset"], -1000.0);
+}
+
+#[test]
+fn test_cli_repeated_values_and_end_of_options() {
+    let result = parse(&["--tag", "a", "--tag=b", "--tag", "3", "--", "--port", "80"]);
+
+    assert_eq!(result["tag"], serde_json::json!(["a", "b", 3]));
+    assert!(result.get("port").is_none());
+}
+
+#[test]
+fn test_cli_nested_keys_override_file() -> Result<(), Box<dyn std::error::Error>> {
+    use gonfig::{ConfigBuilder, ConfigFormat};
+    use std::io::Write;
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    writeln!(
+        file,
+        "database:\n  host: localhost\n  port: 5432\n  pool_size: 5"
+    )?;
+
+    let args = ["--database.host=db1", "--database.pool-size", "10"];
+    let cli = Cli::from_vec(
+        std::iter::once("program")
+            .chain(args)
+            .map(String::from)
+            .collect(),
+    );
+    assert_eq!(
+        cli.origin("database.pool_size").as_deref(),
+        Some("CLI flag --database.pool-size")
+    );
+
+    let merged = ConfigBuilder::new()
+        .with_file_format(file.path(), ConfigFormat::Yaml)?
+        .with_cli_custom(cli)
+        .build_value()?;
+    assert_eq!(
+        merged["database"],
+        serde_json::json!({ "host": "db1", "port": 5432, "pool_size": 10 })
+    );
+
+    // With `-` as separator, mapped keys are left alone
+    let result = Cli::from_vec(vec![
+        "program".into(),
+        "--database-host".into(),
+        "db2".into(),
+        "--log-level".into(),
+        "debug".into(),
+    ])
+    .separator("-")
+    .with_field_mapping("log_level", "log-level")
+    .collect()?;
+    assert_eq!(result["database"]["host"], "db2");
+    assert_eq!(result["log_level"], "debug");
+    assert!(result.get("log").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_flag_counts_for_integer_fields() -> Result<(), Box<dyn std::error::Error>> {
+    use gonfig::{ConfigBuilder, Gonfig, GonfigSchema};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Gonfig)]
+    #[Gonfig(env_prefix = "CLIC")]
+    struct Verbosity {
+        #[serde(default)]
+        verbose: u8,
+        #[serde(default)]
+        debug: bool,
+    }
+
+    let build = |args: &[&str]| {
+        let cli = Cli::from_vec(
+            std::iter::once("program")
+                .chain(args.iter().copied())
+                .map(String::from)
+                .collect(),
+        )
+        .with_field_mapping("verbose", "v");
+        ConfigBuilder::new()
+            .with_schema(Verbosity::gonfig_schema())
+            .with_cli_custom(cli)
+            .build::<Verbosity>()
+    };
+
+    assert_eq!(build(&[])?.verbose, 0);
+    assert_eq!(build(&["-v"])?.verbose, 1);
+    assert_eq!(build(&["-vv"])?.verbose, 2);
+    assert_eq!(build(&["-v", "-v", "-v"])?.verbose, 3);
+    assert!(!build(&["-v"])?.debug);
+    assert!(build(&["--debug"])?.debug);
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_explicit_values_are_not_counted() {
+    let result = parse(&["--debug=true", "--debug=true", "--quiet", "true", "--quiet"]);
+    assert_eq!(result["debug"], true);
+    assert_eq!(result["quiet"], true);
+
+    let result = parse(&["--debug", "--debug=false"]);
+    assert_eq!(result["debug"], false);
+}
+
+#[test]
+fn test_cli_negation_only_for_bare_flags() {
+    let result = parse(&["--no-proxy", "localhost", "--no-color", "--no-cache=1"]);
+
+    assert_eq!(result["no-proxy"], "localhost");
+    assert_eq!(result["color"], false);
+    assert_eq!(result["no-cache"], 1);
+    assert!(result.get("proxy").is_none());
+    assert!(result.get("cache").is_none());
+
+    let cli = Cli::from_vec(
+        ["program", "--no-proxy", "localhost", "input.txt"]
+            .map(String::from)
+            .to_vec(),
+    )
+    .with_positional("input", 0);
+    assert_eq!(cli.collect().unwrap()["input"], "input.txt");
+}
+
+#[test]
+fn test_cli_only_nested_keys_are_normalized() {
+    let cli = Cli::from_vec(
+        ["program", "--max-conn", "5", "--serve.max-conn", "10"]
+            .map(String::from)
+            .to_vec(),
+    );
+
+    assert_eq!(
+        cli.collect().unwrap(),
+        serde_json::json!({ "max-conn": 5, "serve": { "max_conn": 10 } })
+    );
+    assert_eq!(
+        cli.origin("serve.max_conn").as_deref(),
+        Some("CLI flag --serve.max-conn")
+    );
+}
@@ -0,0 +1,5 @@
+#[test]
+fn test_invalid_usage_is_reported() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,103 @@
+use gonfig::{ConfigBuilder, ConfigFiles, Error, Gonfig};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::Write;
+use tempfile::{Builder, NamedTempFile};
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(
+    env_prefix = "CFGF",
+    config_file = "cfgf-missing.toml, cfgf-also-missing.yaml",
+    config_file_env = "CFGF_CONFIG",
+    config_file_cli = "config",
+    config_optional
+)]
+struct OptionalFiles {
+    #[gonfig(default = "\"default\"")]
+    name: String,
+
+    #[gonfig(default = "80")]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "CFGR", config_file = "cfgr-missing.toml")]
+struct RequiredFile {
+    #[gonfig(default = "1")]
+    workers: u32,
+}
+
+fn toml_file(content: &str) -> NamedTempFile {
+    let mut file = Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(file, "{}", content).unwrap();
+    file
+}
+
+#[test]
+fn test_files_from_env_var_are_layered() {
+    // Missing default paths are skipped when optional
+    assert_eq!(
+        OptionalFiles::from_gonfig().unwrap(),
+        OptionalFiles {
+            name: "default".into(),
+            port: 80
+        }
+    );
+
+    let base = toml_file("name = \"base\"\nport = 8080");
+    let local = toml_file("port = 9090");
+    env::set_var(
+        "CFGF_CONFIG",
+        env::join_paths([base.path(), local.path()]).unwrap(),
+    );
+    let layered = OptionalFiles::from_gonfig();
+
+    // Files named explicitly must exist, even when the defaults are optional
+    env::set_var("CFGF_CONFIG", "/nonexistent/cfgf.toml");
+    let missing = OptionalFiles::from_gonfig();
+
+    let broken = toml_file("port = ");
+    env::set_var("CFGF_CONFIG", broken.path());
+    let malformed = OptionalFiles::from_gonfig();
+
+    env::remove_var("CFGF_CONFIG");
+
+    assert_eq!(
+        layered.unwrap(),
+        OptionalFiles {
+            name: "base".into(),
+            port: 9090
+        }
+    );
+    match missing {
+        Err(Error::Config(msg)) => assert!(msg.contains("/nonexistent/cfgf.toml"), "{}", msg),
+        other => panic!("expected config error, got {:?}", other),
+    }
+    match malformed {
+        Err(Error::Serialization(msg)) => assert!(msg.contains("TOML parse error"), "{}", msg),
+        other => panic!("expected parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_required_default_file_missing() {
+    match RequiredFile::from_gonfig() {
+        Err(Error::Config(msg)) => {
+            assert_eq!(msg, "Config file not found: cfgr-missing.toml")
+        }
+        other => panic!("expected config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_builder_config_files() {
+    let file = toml_file("workers = 4");
+
+    let config: RequiredFile = ConfigBuilder::new()
+        .with_config_files(&ConfigFiles::new().path(file.path()))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(config.workers, 4);
+}
@@ -0,0 +1,152 @@
+use gonfig::{ConfigBuilder, Environment, Error, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "CONSTR")]
+struct ServerConfig {
+    #[gonfig(default = "8080")]
+    port: u16,
+
+    #[gonfig(conflicts_with = "port")]
+    socket_path: Option<String>,
+
+    enable_tls: bool,
+
+    #[gonfig(required_if = "enable_tls", requires = "tls_key")]
+    tls_cert: Option<String>,
+
+    tls_key: Option<String>,
+
+    auth: Auth,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Auth {
+    mode: String,
+
+    #[gonfig(required_if = "mode=token")]
+    token: Option<String>,
+}
+
+fn build(value: serde_json::Value) -> gonfig::Result<ServerConfig> {
+    ConfigBuilder::new()
+        .with_schema(ServerConfig::gonfig_schema())
+        .with_defaults(value)?
+        .build()
+}
+
+fn validation_message<T: std::fmt::Debug>(result: gonfig::Result<T>) -> String {
+    match result {
+        Err(Error::Validation(msg)) => msg,
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_constraints_satisfied() {
+    let config = build(json!({
+        "port": 8080,
+        "enable_tls": true,
+        "tls_cert": "cert.pem",
+        "tls_key": "key.pem",
+        "auth": { "mode": "token", "token": "secret" }
+    }))
+    .unwrap();
+
+    assert_eq!(config.tls_cert.as_deref(), Some("cert.pem"));
+}
+
+#[test]
+fn test_required_if_and_requires() {
+    let msg = validation_message(build(json!({
+        "port": 8080,
+        "enable_tls": true,
+        "auth": { "mode": "token" }
+    })));
+
+    assert!(
+        msg.contains(
+            "tls_cert is required when enable_tls is set (enable_tls set by default value)"
+        ),
+        "{}",
+        msg
+    );
+    assert!(
+        msg.contains("auth.token is required when auth.mode is token"),
+        "{}",
+        msg
+    );
+
+    let msg = validation_message(build(json!({
+        "port": 8080,
+        "enable_tls": false,
+        "tls_cert": "cert.pem",
+        "auth": { "mode": "none" }
+    })));
+    assert!(
+        msg.contains("tls_cert requires tls_key to be set"),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_conflict_names_sources() {
+    env::set_var("CONSTR_SOCKET_PATH", "/tmp/app.sock");
+
+    // The default port alone does not conflict
+    let config = ServerConfig::from_gonfig_with_builder(
+        ConfigBuilder::new()
+            .with_defaults(json!({ "enable_tls": false, "auth": { "mode": "none" } }))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.socket_path.as_deref(), Some("/tmp/app.sock"));
+
+    env::set_var("CONSTR_PORT", "9000");
+    let result = ServerConfig::from_gonfig_with_builder(
+        ConfigBuilder::new()
+            .with_defaults(json!({ "enable_tls": false, "auth": { "mode": "none" } }))
+            .unwrap(),
+    );
+
+    env::remove_var("CONSTR_SOCKET_PATH");
+    env::remove_var("CONSTR_PORT");
+
+    let msg = validation_message(result);
+    assert!(
+        msg.contains(
+            "socket_path conflicts with port (socket_path set by env var CONSTR_SOCKET_PATH, \
+             port set by env var CONSTR_PORT)"
+        ),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_builder_constraints() {
+    env::set_var("CONSTR_B_USER", "admin");
+
+    let result = ConfigBuilder::new()
+        .with_defaults(json!({ "password_file": "/run/secret" }))
+        .unwrap()
+        .with_env_custom(Environment::new().with_prefix("CONSTR_B"))
+        .required_if("password", "user")
+        .conflicts_with("password", "password_file")
+        .requires("user", "realm")
+        .build_value();
+
+    env::remove_var("CONSTR_B_USER");
+
+    let msg = validation_message(result);
+    assert!(
+        msg.contains("password is required when user is set (user set by env var CONSTR_B_USER)"),
+        "{}",
+        msg
+    );
+    assert!(msg.contains("user requires realm to be set"), "{}", msg);
+    assert!(!msg.contains("conflicts"), "{}", msg);
+}
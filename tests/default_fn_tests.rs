@@ -0,0 +1,193 @@
+use gonfig::{ConfigBuilder, ConfigFormat, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+fn default_workers() -> usize {
+    4
+}
+
+fn default_data_dir() -> PathBuf {
+    PathBuf::from("/var/lib/app")
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "DEFN")]
+struct AppConfig {
+    /// Worker threads
+    #[gonfig(default_fn = "default_workers")]
+    workers: usize,
+
+    #[gonfig(default_fn = "default_data_dir")]
+    data_dir: PathBuf,
+
+    #[gonfig(default)]
+    tags: Vec<String>,
+
+    #[gonfig(default)]
+    verbose: bool,
+
+    server: Server,
+
+    /// Set by the parent's `Default` as a whole
+    #[gonfig(default)]
+    limits: Limits,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(default)]
+struct Server {
+    host: String,
+    port: u16,
+
+    #[gonfig(default = "30")]
+    timeout_secs: u64,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Limits {
+    max_body: u64,
+    max_connections: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_body: 1024,
+            max_connections: 100,
+        }
+    }
+}
+
+#[test]
+fn test_default_fn_and_default_trait() {
+    let config = AppConfig::from_gonfig().unwrap();
+
+    assert_eq!(config.workers, 4);
+    assert_eq!(config.data_dir, PathBuf::from("/var/lib/app"));
+    assert!(config.tags.is_empty());
+    assert!(!config.verbose);
+    assert_eq!(config.limits, Limits::default());
+
+    // Field defaults take precedence over the struct's `Default`
+    assert_eq!(
+        config.server,
+        Server {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            timeout_secs: 30,
+        }
+    );
+
+    // Nested defaults sit below the other sources
+    std::env::set_var("DEFN_SERVER_PORT", "9000");
+    std::env::set_var("DEFN_WORKERS", "16");
+    let config = AppConfig::from_gonfig().unwrap();
+    std::env::remove_var("DEFN_SERVER_PORT");
+    std::env::remove_var("DEFN_WORKERS");
+
+    assert_eq!(config.workers, 16);
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.server.host, "0.0.0.0");
+}
+
+#[test]
+fn test_defaults_in_schema_descriptions() {
+    // Defaults show up wherever the schema is described
+    let defaults = AppConfig::gonfig_schema().defaults();
+    assert_eq!(defaults["server"]["timeout_secs"], 30);
+    assert_eq!(defaults["limits"]["max_connections"], 100);
+
+    let mapping = AppConfig::gonfig_schema()
+        .field_mappings()
+        .into_iter()
+        .find(|m| m.path == "workers")
+        .unwrap();
+    assert_eq!(mapping.default_value, Some(serde_json::json!(4)));
+
+    let template = AppConfig::template(ConfigFormat::Toml);
+    assert!(template.contains("workers = 4\n"), "{}", template);
+    assert!(
+        template.contains("[server]\nhost = \"0.0.0.0\"\nport = 8080\ntimeout_secs = 30\n"),
+        "{}",
+        template
+    );
+
+    let value = ConfigBuilder::new()
+        .with_defaults(Server::gonfig_schema().defaults())
+        .unwrap()
+        .build_value()
+        .unwrap();
+    assert_eq!(value["timeout_secs"], 30);
+    assert_eq!(value["host"], "0.0.0.0");
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Fast,
+    Safe,
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "DEFL")]
+struct LiteralDefaults {
+    #[gonfig(default = "-40")]
+    offset: i8,
+
+    #[gonfig(default = "0.5")]
+    ratio: f32,
+
+    #[gonfig(default = "null")]
+    limit: Option<u32>,
+
+    #[gonfig(default = "x")]
+    separator: char,
+
+    #[gonfig(default = "[80, 443]")]
+    ports: Vec<u16>,
+
+    #[gonfig(default = "\"8080\"")]
+    port_name: String,
+
+    #[gonfig(default = "info", one_of = ["debug", "info"])]
+    level: String,
+
+    #[gonfig(default = "safe")]
+    mode: Mode,
+
+    #[gonfig(default = "18446744073709551615")]
+    total: u128,
+
+    #[gonfig(default = "-9223372036854775808")]
+    floor: i128,
+}
+
+#[test]
+fn test_literal_defaults_matching_their_types() {
+    let config = LiteralDefaults::from_gonfig().unwrap();
+    assert_eq!(
+        config,
+        LiteralDefaults {
+            offset: -40,
+            ratio: 0.5,
+            limit: None,
+            separator: 'x',
+            ports: vec![80, 443],
+            port_name: "8080".to_string(),
+            level: "info".to_string(),
+            mode: Mode::Safe,
+            total: u64::MAX as u128,
+            floor: i64::MIN as i128,
+        }
+    );
+}
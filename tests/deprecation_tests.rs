@@ -0,0 +1,160 @@
+use gonfig::{Cli, ConfigBuilder, ConfigFormat, Error, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "DEPR")]
+struct AppConfig {
+    #[gonfig(deprecated_env = "DEPR_LEGACY_DB_URL", deprecated_key = "db_url")]
+    database_url: String,
+
+    server: Server,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Server {
+    #[gonfig(alias = "listen_port")]
+    port: u16,
+}
+
+#[test]
+fn test_deprecated_mappings() {
+    let mappings = AppConfig::gonfig_schema().field_mappings();
+
+    let url = mappings.iter().find(|m| m.path == "database_url").unwrap();
+    assert_eq!(url.deprecated.env_keys, vec!["DEPR_LEGACY_DB_URL"]);
+    assert_eq!(url.deprecated.paths, vec!["db_url"]);
+
+    let port = mappings.iter().find(|m| m.path == "server.port").unwrap();
+    assert_eq!(port.deprecated.env_keys, vec!["DEPR_SERVER_LISTEN_PORT"]);
+    assert_eq!(port.deprecated.cli_keys, vec!["server-listen-port"]);
+    assert_eq!(port.deprecated.paths, vec!["server.listen_port"]);
+}
+
+#[test]
+fn test_old_names_are_read_with_warnings() -> Result<(), Box<dyn std::error::Error>> {
+    env::set_var("DEPR_LEGACY_DB_URL", "postgres://legacy");
+
+    let mut file = NamedTempFile::new()?;
+    writeln!(file, "[server]\nlisten_port = 8080")?;
+
+    let result = ConfigBuilder::new()
+        .with_file_format(file.path(), ConfigFormat::Toml)?
+        .with_env("DEPR")
+        .with_schema(AppConfig::gonfig_schema())
+        .build_with_warnings::<AppConfig>();
+
+    env::remove_var("DEPR_LEGACY_DB_URL");
+
+    let (config, warnings) = result?;
+    assert_eq!(config.database_url, "postgres://legacy");
+    assert_eq!(config.server.port, 8080);
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.starts_with("key server.listen_port in config file")
+                && w.ends_with("is deprecated, use key server.port instead")),
+        "{:?}",
+        warnings
+    );
+    assert!(
+        warnings.contains(
+            &"env var DEPR_LEGACY_DB_URL is deprecated, use env var DEPR_DATABASE_URL instead"
+                .to_string()
+        ),
+        "{:?}",
+        warnings
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_current_name_wins_and_strict_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = NamedTempFile::new()?;
+    writeln!(
+        file,
+        "db_url = \"postgres://old\"\ndatabase_url = \"postgres://new\"\n[server]\nport = 1"
+    )?;
+
+    let builder = || -> gonfig::Result<ConfigBuilder> {
+        Ok(ConfigBuilder::new()
+            .with_file_format(file.path(), ConfigFormat::Toml)?
+            .with_cli_custom(Cli::from_vec(vec![
+                "program".into(),
+                "--server-listen-port".into(),
+                "9000".into(),
+            ]))
+            .with_schema(AppConfig::gonfig_schema()))
+    };
+
+    let (config, warnings) = builder()?.build_with_warnings::<AppConfig>()?;
+    assert_eq!(config.database_url, "postgres://new");
+    assert_eq!(config.server.port, 9000);
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.ends_with("is deprecated and ignored because key database_url is set")),
+        "{:?}",
+        warnings
+    );
+    assert!(
+        warnings.contains(
+            &"CLI flag --server-listen-port is deprecated, use --server-port instead".to_string()
+        ),
+        "{:?}",
+        warnings
+    );
+
+    match builder()?.deny_deprecated(true).build::<AppConfig>() {
+        Err(Error::Validation(msg)) => {
+            assert!(
+                msg.contains("--server-listen-port is deprecated"),
+                "{}",
+                msg
+            )
+        }
+        other => panic!("expected validation error, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "DEPC", allow_cli)]
+struct CliConfig {
+    #[gonfig(alias = "db_url")]
+    database_url: String,
+}
+
+#[test]
+fn test_alias_flag_with_clap_command() -> Result<(), Box<dyn std::error::Error>> {
+    let config = CliConfig::from_gonfig_with_args(
+        ConfigBuilder::new(),
+        ["app", "--db-url", "postgres://old"],
+    )?;
+    assert_eq!(config.database_url, "postgres://old");
+
+    let schema = CliConfig::gonfig_schema();
+    let help = Cli::command(&schema).render_help().to_string();
+    assert!(!help.contains("--db-url"), "{}", help);
+
+    let cli = Cli::from_command_args(
+        Cli::command(&schema),
+        &schema,
+        ["app", "--db-url", "postgres://old"],
+    )?;
+    let (config, warnings) = ConfigBuilder::new()
+        .with_cli_custom(cli)
+        .with_schema(schema)
+        .build_with_warnings::<CliConfig>()?;
+    assert_eq!(config.database_url, "postgres://old");
+    assert_eq!(
+        warnings,
+        vec!["CLI flag --db-url is deprecated, use --database-url instead".to_string()]
+    );
+
+    Ok(())
+}
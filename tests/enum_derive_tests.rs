@@ -0,0 +1,189 @@
+use gonfig::{ConfigBuilder, Error, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Storage {
+    Local { path: String },
+    S3 { bucket: String, region: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[serde(tag = "type", content = "settings")]
+enum Cache {
+    #[serde(rename = "memory")]
+    Memory { capacity: u32 },
+    #[serde(rename = "redis")]
+    Redis(RedisSettings),
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct RedisSettings {
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "ENUMS")]
+struct ServiceConfig {
+    #[gonfig(default = "\"info\"")]
+    log_level: LogLevel,
+    storage: Storage,
+    cache: Cache,
+}
+
+#[test]
+fn test_enum_field_mappings() {
+    let mappings = ServiceConfig::gonfig_schema().field_mappings();
+    let env_key = |path: &str| {
+        mappings
+            .iter()
+            .find(|m| m.path == path)
+            .map(|m| m.env_key.as_str())
+    };
+
+    assert_eq!(env_key("log_level"), Some("ENUMS_LOG_LEVEL"));
+    assert_eq!(env_key("storage.kind"), Some("ENUMS_STORAGE_KIND"));
+    assert_eq!(env_key("storage.path"), Some("ENUMS_STORAGE_PATH"));
+    assert_eq!(env_key("storage.bucket"), Some("ENUMS_STORAGE_BUCKET"));
+    assert_eq!(env_key("cache.type"), Some("ENUMS_CACHE_TYPE"));
+    assert_eq!(
+        env_key("cache.settings.capacity"),
+        Some("ENUMS_CACHE_SETTINGS_CAPACITY")
+    );
+    assert_eq!(
+        env_key("cache.settings.url"),
+        Some("ENUMS_CACHE_SETTINGS_URL")
+    );
+}
+
+#[test]
+fn test_enum_selected_from_env_case_insensitive() {
+    env::set_var("ENUMS_LOG_LEVEL", "WARN");
+    env::set_var("ENUMS_STORAGE_KIND", "S3");
+    env::set_var("ENUMS_STORAGE_BUCKET", "assets");
+    env::set_var("ENUMS_STORAGE_REGION", "eu-west-1");
+    env::set_var("ENUMS_CACHE_TYPE", "Redis");
+    env::set_var("ENUMS_CACHE_SETTINGS_URL", "redis://cache");
+
+    let config = ServiceConfig::from_gonfig().unwrap();
+
+    assert_eq!(config.log_level, LogLevel::Warn);
+    assert_eq!(
+        config.storage,
+        Storage::S3 {
+            bucket: "assets".into(),
+            region: "eu-west-1".into()
+        }
+    );
+    assert_eq!(
+        config.cache,
+        Cache::Redis(RedisSettings {
+            url: "redis://cache".into()
+        })
+    );
+
+    for var in [
+        "ENUMS_LOG_LEVEL",
+        "ENUMS_STORAGE_KIND",
+        "ENUMS_STORAGE_BUCKET",
+        "ENUMS_STORAGE_REGION",
+        "ENUMS_CACHE_TYPE",
+        "ENUMS_CACHE_SETTINGS_URL",
+    ] {
+        env::remove_var(var);
+    }
+}
+
+#[test]
+fn test_enum_from_file_values() {
+    let config: ServiceConfig = ConfigBuilder::new()
+        .with_schema(ServiceConfig::gonfig_schema())
+        .with_defaults(json!({
+            "log_level": "Debug",
+            "storage": { "kind": "LOCAL", "path": "/var/data" },
+            "cache": { "type": "MEMORY", "settings": { "capacity": 64 } }
+        }))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(config.log_level, LogLevel::Debug);
+    assert_eq!(
+        config.storage,
+        Storage::Local {
+            path: "/var/data".into()
+        }
+    );
+    assert_eq!(config.cache, Cache::Memory { capacity: 64 });
+}
+
+#[test]
+fn test_unknown_variant_lists_allowed() {
+    let result: Result<ServiceConfig, _> = ConfigBuilder::new()
+        .with_schema(ServiceConfig::gonfig_schema())
+        .with_defaults(json!({
+            "storage": { "kind": "gcs" },
+        }))
+        .unwrap()
+        .build();
+
+    match result {
+        Err(Error::Config(msg)) => {
+            assert!(msg.contains("'gcs'"), "{}", msg);
+            assert!(msg.contains("storage"), "{}", msg);
+            assert!(msg.contains("local, s3"), "{}", msg);
+        }
+        other => panic!("expected config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_root_enum() {
+    let level: LogLevel = ConfigBuilder::new()
+        .with_schema(LogLevel::gonfig_schema())
+        .with_defaults(json!("INFO"))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(level, LogLevel::Info);
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "APP")]
+struct AppConfig {
+    storage: Storage,
+}
+
+#[test]
+fn test_enum_tag_with_section_separator() {
+    let mappings = AppConfig::gonfig_schema().field_mappings();
+    let kind = mappings.iter().find(|m| m.path == "storage.kind").unwrap();
+    assert_eq!(kind.env_key, "APP_STORAGE_KIND");
+    assert_eq!(kind.env_aliases, ["APP_STORAGE__KIND"]);
+
+    env::set_var("APP_STORAGE__KIND", "local");
+    env::set_var("APP_STORAGE_PATH", "/var/data");
+
+    let config = AppConfig::from_gonfig();
+
+    env::remove_var("APP_STORAGE__KIND");
+    env::remove_var("APP_STORAGE_PATH");
+
+    assert_eq!(
+        config.unwrap().storage,
+        Storage::Local {
+            path: "/var/data".into()
+        }
+    );
+}
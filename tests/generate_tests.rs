@@ -0,0 +1,137 @@
+use gonfig::generate::Shell;
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+/// Serve the application
+#[allow(dead_code)]
+#[derive(Debug, Deserialize, Gonfig)]
+#[Gonfig(
+    env_prefix = "GENR",
+    allow_cli,
+    config_file = "/etc/genr/genr.toml",
+    config_file_env = "GENR_CONFIG"
+)]
+struct ServeConfig {
+    /// Port to listen on
+    #[gonfig(short = 'p', default = "8080")]
+    port: u16,
+
+    #[gonfig(env_name = "DATABASE_URL")]
+    database_url: String,
+
+    log: Log,
+
+    #[gonfig(no_env)]
+    token: Option<String>,
+
+    #[gonfig(sources = "file,env")]
+    secret: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize, Gonfig)]
+struct Log {
+    /// Log level
+    level: String,
+}
+
+#[test]
+fn test_completions_for_each_shell() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let mut script = Vec::new();
+        ServeConfig::gonfig_completions(shell, &mut script);
+        let script = String::from_utf8(script).unwrap();
+
+        assert!(script.contains("database-url"), "{:?}: {}", shell, script);
+        assert!(script.contains("log-level"), "{:?}: {}", shell, script);
+        assert!(script.contains("print-config"), "{:?}: {}", shell, script);
+    }
+}
+
+#[test]
+fn test_man_page_sections() {
+    let mut page = Vec::new();
+    ServeConfig::gonfig_man_page(&mut page).unwrap();
+    let page = String::from_utf8(page).unwrap();
+
+    for section in [
+        ".SH NAME",
+        ".SH SYNOPSIS",
+        ".SH OPTIONS",
+        ".SH ENVIRONMENT",
+        ".SH FILES",
+    ] {
+        assert!(page.contains(section), "missing {}: {}", section, page);
+    }
+    assert!(page.contains("Serve the application"), "{}", page);
+
+    let environment = &page[page.find(".SH ENVIRONMENT").unwrap()..page.find(".SH FILES").unwrap()];
+    assert!(environment.contains("GENR_PORT"), "{}", environment);
+    assert!(
+        environment.contains("Port to listen on [default: 8080]"),
+        "{}",
+        environment
+    );
+    assert!(environment.contains("DATABASE_URL"), "{}", environment);
+    assert!(environment.contains("GENR_LOG_LEVEL"), "{}", environment);
+    assert!(environment.contains("GENR_CONFIG"), "{}", environment);
+
+    let files = &page[page.find(".SH FILES").unwrap()..];
+    assert!(files.contains("/etc/genr/genr.toml"), "{}", files);
+    assert!(
+        files.contains("unless \\-\\-config or GENR_CONFIG is given"),
+        "{}",
+        files
+    );
+}
+
+#[test]
+fn test_env_reference_formats() {
+    let reference = ServeConfig::env_reference();
+    let names: Vec<&str> = reference.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["GENR_PORT", "DATABASE_URL", "GENR_LOG_LEVEL", "GENR_SECRET"]
+    );
+    assert_eq!(reference.vars[3].cli_flag, None);
+
+    let markdown = reference.to_markdown();
+    assert!(
+        markdown.starts_with("| Variable | Type | Default | Description | CLI flag | File key |\n"),
+        "{}",
+        markdown
+    );
+    assert!(
+        markdown
+            .contains("| `GENR_LOG_LEVEL` | string |  | Log level | `--log-level` | `log.level` |"),
+        "{}",
+        markdown
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&reference.to_json().unwrap()).unwrap();
+    assert_eq!(
+        json[0],
+        serde_json::json!({
+            "name": "GENR_PORT",
+            "type": "integer",
+            "default": "8080",
+            "description": "Port to listen on",
+            "cli_flag": "--port",
+            "file_key": "port",
+        })
+    );
+
+    let table = reference.to_string();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(
+        lines[0].starts_with("VARIABLE        TYPE     DEFAULT  CLI FLAG"),
+        "{}",
+        table
+    );
+    assert!(
+        lines[2].starts_with("DATABASE_URL    string   -        --database-url"),
+        "{}",
+        table
+    );
+}
@@ -0,0 +1,76 @@
+use gonfig::{ConfigBuilder, Gonfig};
+use serde::Deserialize;
+use std::io::Write;
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "GBLD", config_file_env = "GBLD_CONFIG")]
+struct AppConfig {
+    #[gonfig(default = "\"app\"")]
+    name: String,
+
+    #[gonfig(default = "8080")]
+    port: u16,
+
+    workers: usize,
+}
+
+#[derive(Debug, Deserialize, Gonfig)]
+#[Gonfig(env_prefix = "GBLM", config_file_env = "GBLM_CONFIG")]
+struct StrictConfig {
+    #[allow(dead_code)]
+    port: u16,
+}
+
+fn toml_file(content: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(file, "{}", content).unwrap();
+    file
+}
+
+#[test]
+fn test_gonfig_builder_keeps_files_and_defaults() {
+    let derived = toml_file("workers = 4\nport = 81\n");
+    let extra = toml_file("port = 9000\n");
+
+    std::env::set_var("GBLD_CONFIG", derived.path());
+    let builder = AppConfig::gonfig_builder();
+    std::env::remove_var("GBLD_CONFIG");
+
+    // Sources appended to the derive's setup keep its files and defaults
+    let config: AppConfig = builder.with_file(extra.path()).unwrap().build().unwrap();
+    assert_eq!(
+        config,
+        AppConfig {
+            name: "app".to_string(),
+            port: 9000,
+            workers: 4,
+        }
+    );
+
+    // The same sources as `from_gonfig`
+    std::env::set_var("GBLD_CONFIG", derived.path());
+    let config = AppConfig::from_gonfig_with_builder(ConfigBuilder::new());
+    std::env::remove_var("GBLD_CONFIG");
+    assert_eq!(config.unwrap().port, 81);
+}
+
+#[test]
+fn test_gonfig_builder_reports_errors_at_build() {
+    std::env::set_var("GBLM_CONFIG", "/nonexistent/gonfig/strict.toml");
+    let builder = StrictConfig::gonfig_builder();
+    std::env::remove_var("GBLM_CONFIG");
+
+    let err = builder.build::<StrictConfig>().unwrap_err();
+    assert!(
+        err.to_string().contains("/nonexistent/gonfig/strict.toml"),
+        "{}",
+        err
+    );
+
+    let invalid = toml_file("port = [");
+    let err = ConfigBuilder::new()
+        .with_file_deferred(invalid.path())
+        .build_value()
+        .unwrap_err();
+    assert!(err.to_string().contains("TOML parse error"), "{}", err);
+}
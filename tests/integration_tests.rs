@@ -47,12 +47,13 @@ struct CliEnabledConfig {
 #[test]
 fn test_derive_macro_with_cli() {
     // This test demonstrates that CLI support is enabled
-    // In real usage, CLI args would come from command line
+    // In real usage, CLI args would come from command line; the test
+    // harness's own flags would be rejected as unknown, so pass none
     env::set_var("TEST_HOST", "localhost");
     env::set_var("TEST_PORT", "3000");
     env::set_var("TEST_SSL_ENABLED", "false");
 
-    let config = CliEnabledConfig::from_gonfig().unwrap();
+    let config = CliEnabledConfig::from_gonfig_with_args(ConfigBuilder::new(), ["test"]).unwrap();
 
     assert_eq!(config.host, "localhost");
     assert_eq!(config.port, 3000);
@@ -0,0 +1,167 @@
+use gonfig::{Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "NEST")]
+struct AppConfig {
+    name: String,
+    database: DatabaseConfig,
+
+    #[gonfig(env_name = "NEST_CACHE_SETTINGS", cli_name = "cache")]
+    cache: CacheConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "IGNORED_WHEN_NESTED")]
+struct DatabaseConfig {
+    host: String,
+
+    #[gonfig(default = "5432")]
+    port: u16,
+
+    pool: PoolConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct PoolConfig {
+    #[gonfig(default = "10")]
+    size: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct CacheConfig {
+    ttl: u64,
+
+    #[gonfig(env_name = "NEST_ABSOLUTE_CACHE_URL")]
+    url: String,
+}
+
+#[test]
+fn test_nested_field_mappings() {
+    let mappings = AppConfig::gonfig_schema().field_mappings();
+    let find = |path: &str| mappings.iter().find(|m| m.path == path).unwrap();
+
+    assert_eq!(find("name").env_key, "NEST_NAME");
+    assert_eq!(find("database.host").env_key, "NEST_DATABASE_HOST");
+    assert_eq!(find("database.host").cli_key, "database-host");
+    assert_eq!(
+        find("database.pool.size").env_key,
+        "NEST_DATABASE_POOL_SIZE"
+    );
+    assert_eq!(find("database.pool.size").cli_key, "database-pool-size");
+    assert_eq!(find("cache.ttl").env_key, "NEST_CACHE_SETTINGS_TTL");
+    assert_eq!(find("cache.ttl").cli_key, "cache-ttl");
+    assert_eq!(find("cache.url").env_key, "NEST_ABSOLUTE_CACHE_URL");
+
+    // Only leaf fields are mapped
+    assert!(mappings.iter().all(|m| m.path != "database"));
+}
+
+#[test]
+fn test_nested_struct_standalone_uses_own_prefix() {
+    let mappings = DatabaseConfig::gonfig_schema().field_mappings();
+    assert_eq!(mappings[0].env_key, "IGNORED_WHEN_NESTED_HOST");
+}
+
+#[test]
+fn test_nested_from_gonfig() {
+    env::set_var("NEST_NAME", "nested-app");
+    env::set_var("NEST_DATABASE_HOST", "db.internal");
+    env::set_var("NEST_CACHE_SETTINGS_TTL", "60");
+    env::set_var("NEST_ABSOLUTE_CACHE_URL", "redis://cache");
+
+    let config = AppConfig::from_gonfig().unwrap();
+
+    assert_eq!(config.name, "nested-app");
+    assert_eq!(config.database.host, "db.internal");
+    // Defaults are applied at their nested path
+    assert_eq!(config.database.port, 5432);
+    assert_eq!(config.database.pool.size, 10);
+    assert_eq!(config.cache.ttl, 60);
+    assert_eq!(config.cache.url, "redis://cache");
+
+    env::remove_var("NEST_NAME");
+    env::remove_var("NEST_DATABASE_HOST");
+    env::remove_var("NEST_CACHE_SETTINGS_TTL");
+    env::remove_var("NEST_ABSOLUTE_CACHE_URL");
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "NESTO", allow_cli)]
+struct OptionalConfig {
+    #[gonfig(default = "\"app\"")]
+    name: String,
+    database: Option<DatabaseConfig>,
+}
+
+#[test]
+fn test_optional_nested_section() {
+    let schema = OptionalConfig::gonfig_schema();
+    let mappings = schema.field_mappings();
+    assert!(mappings.iter().all(|m| m.path != "database"));
+    let host = mappings.iter().find(|m| m.path == "database.host").unwrap();
+    assert_eq!(host.env_key, "NESTO_DATABASE_HOST");
+    assert_eq!(host.cli_key, "database-host");
+
+    let config = OptionalConfig::from_gonfig_with_args(gonfig::ConfigBuilder::new(), ["app"]);
+    assert_eq!(config.unwrap().database, None);
+
+    // Setting one field fills the others from their defaults
+    let config = OptionalConfig::from_gonfig_with_args(
+        gonfig::ConfigBuilder::new(),
+        ["app", "--database-host", "db1"],
+    )
+    .unwrap();
+    assert_eq!(
+        config.database,
+        Some(DatabaseConfig {
+            host: "db1".into(),
+            port: 5432,
+            pool: PoolConfig { size: 10 },
+        })
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "NESTJ")]
+struct SectionConfig {
+    database: DatabaseConfig,
+
+    #[gonfig(env_name = "NESTJ_STORE")]
+    store: PoolConfig,
+}
+
+#[test]
+fn test_nested_section_from_json_env_var() {
+    let sections = SectionConfig::gonfig_schema().section_env_keys();
+    assert!(sections.contains(&("database".to_string(), "NESTJ_DATABASE".to_string())));
+    assert!(sections.contains(&(
+        "database.pool".to_string(),
+        "NESTJ_DATABASE_POOL".to_string()
+    )));
+    assert!(sections.contains(&("store".to_string(), "NESTJ_STORE".to_string())));
+
+    env::set_var(
+        "NESTJ_DATABASE",
+        r#"{"host": "json-host", "port": 1, "pool": {"size": 3}}"#,
+    );
+    env::set_var("NESTJ_DATABASE_PORT", "6543");
+    env::set_var("NESTJ_STORE", r#"{"size": 7}"#);
+
+    let config = SectionConfig::from_gonfig().unwrap();
+    assert_eq!(
+        config.database,
+        DatabaseConfig {
+            host: "json-host".into(),
+            // The field's own variable takes precedence over the section
+            port: 6543,
+            pool: PoolConfig { size: 3 },
+        }
+    );
+    assert_eq!(config.store, PoolConfig { size: 7 });
+
+    env::remove_var("NESTJ_DATABASE");
+    env::remove_var("NESTJ_DATABASE_PORT");
+    env::remove_var("NESTJ_STORE");
+}
@@ -0,0 +1,121 @@
+use gonfig::{ConfigBuilder, Gonfig};
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Write;
+use std::net::{AddrParseError, SocketAddr};
+
+fn parse_peers(raw: &str) -> Result<Vec<SocketAddr>, AddrParseError> {
+    raw.split(',').map(|peer| peer.trim().parse()).collect()
+}
+
+fn parse_duration(raw: &str) -> Result<u64, String> {
+    let (number, unit) = raw.split_at(raw.trim_end_matches(char::is_alphabetic).len());
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration `{}`", raw))?;
+    match unit {
+        "" | "s" => Ok(number),
+        "m" => Ok(number * 60),
+        _ => Err(format!("unknown unit `{}`", unit)),
+    }
+}
+
+fn parse_version(raw: &str) -> Result<(u32, u32), String> {
+    let (major, minor) = raw.split_once('.').ok_or("expected MAJOR.MINOR")?;
+    Ok((
+        major.parse().map_err(|_| "invalid major version")?,
+        minor.parse().map_err(|_| "invalid minor version")?,
+    ))
+}
+
+/// Levels are names, or numbers in files
+fn parse_level(value: &Value) -> Result<String, String> {
+    const LEVELS: [&str; 4] = ["error", "warn", "info", "debug"];
+    match value {
+        Value::Number(n) => n
+            .as_u64()
+            .and_then(|n| LEVELS.get(n as usize))
+            .map(|level| level.to_string())
+            .ok_or_else(|| format!("no level {}", n)),
+        Value::String(s) if LEVELS.contains(&s.as_str()) => Ok(s.clone()),
+        other => Err(format!("unknown level {}", other)),
+    }
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "PRSW", allow_cli)]
+struct AppConfig {
+    #[gonfig(parse_with = "parse_peers")]
+    peers: Vec<SocketAddr>,
+
+    #[gonfig(parse_with = "parse_duration", default = "30s")]
+    timeout_secs: u64,
+
+    #[gonfig(parse_with = "parse_version")]
+    api_version: (u32, u32),
+
+    #[gonfig(parse_with = "parse_level")]
+    level: String,
+}
+
+#[derive(Debug, Deserialize, Gonfig)]
+#[Gonfig(env_prefix = "PRSE")]
+struct PeersConfig {
+    #[allow(dead_code)]
+    #[gonfig(parse_with = "parse_peers")]
+    peers: Vec<SocketAddr>,
+}
+
+#[test]
+fn test_parse_with_reads_raw_values_from_every_source() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(file, "peers = \"10.0.0.1:80, 10.0.0.2:81\"\nlevel = 1\n").unwrap();
+    let builder = || ConfigBuilder::new().with_file(file.path()).unwrap();
+
+    std::env::set_var("PRSW_API_VERSION", "1.10");
+    let config = AppConfig::from_gonfig_with_args(builder(), ["app", "--level", "debug"]);
+    let overridden = AppConfig::from_gonfig_with_args(builder(), ["app", "--timeout-secs", "5m"]);
+    std::env::remove_var("PRSW_API_VERSION");
+
+    assert_eq!(
+        config.unwrap(),
+        AppConfig {
+            peers: vec![
+                "10.0.0.1:80".parse().unwrap(),
+                "10.0.0.2:81".parse().unwrap()
+            ],
+            timeout_secs: 30,
+            api_version: (1, 10),
+            level: "debug".to_string(),
+        }
+    );
+
+    let overridden = overridden.unwrap();
+    assert_eq!(overridden.timeout_secs, 300);
+    assert_eq!(overridden.level, "warn");
+}
+
+#[test]
+fn test_parse_with_errors_name_the_source() {
+    std::env::set_var("PRSE_PEERS", "10.0.0.1:80,nope");
+    let err = PeersConfig::from_gonfig().unwrap_err();
+    std::env::remove_var("PRSE_PEERS");
+
+    assert_eq!(
+        err.to_string(),
+        "Validation error: peers: invalid socket address syntax (set by env var PRSE_PEERS)"
+    );
+
+    let err = ConfigBuilder::new()
+        .with_defaults(serde_json::json!({ "peers": ["10.0.0.1:80"] }))
+        .unwrap()
+        .with_schema(<PeersConfig as gonfig::GonfigSchema>::gonfig_schema())
+        .build::<PeersConfig>()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("peers: expected a string, found [\"10.0.0.1:80\"]"),
+        "{}",
+        err
+    );
+}
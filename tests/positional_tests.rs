@@ -0,0 +1,79 @@
+use gonfig::{Cli, ConfigBuilder, ConfigSource, Gonfig};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Gonfig)]
+#[Gonfig(env_prefix = "POSN", allow_cli)]
+struct DeployConfig {
+    /// Environment to deploy to
+    #[gonfig(positional)]
+    target: String,
+
+    /// Hosts to deploy to
+    #[gonfig(positional, length(min = 1))]
+    hosts: Vec<String>,
+
+    #[gonfig(default = "false")]
+    dry_run: bool,
+}
+
+#[test]
+fn test_positional_fields_from_command_line() {
+    let config = DeployConfig::from_gonfig_with_args(
+        ConfigBuilder::new(),
+        ["deploy", "staging", "web1", "web2", "--dry-run"],
+    )
+    .unwrap();
+
+    assert_eq!(config.target, "staging");
+    assert_eq!(config.hosts, ["web1", "web2"]);
+    assert!(config.dry_run);
+
+    let help = DeployConfig::gonfig_command().render_help().to_string();
+    assert!(help.contains("[TARGET]"), "{}", help);
+    assert!(help.contains("[HOSTS]..."), "{}", help);
+}
+
+#[test]
+fn test_positional_fields_merge_and_validate() {
+    // Positional arguments override the environment like flags do
+    std::env::set_var("POSN_TARGET", "production");
+    std::env::set_var("POSN_HOSTS", "[\"db1\"]");
+    let config = DeployConfig::from_gonfig_with_args(ConfigBuilder::new(), ["deploy"]).unwrap();
+    assert_eq!(config.target, "production");
+    assert_eq!(config.hosts, ["db1"]);
+
+    let config =
+        DeployConfig::from_gonfig_with_args(ConfigBuilder::new(), ["deploy", "staging"]).unwrap();
+    assert_eq!(config.target, "staging");
+    assert_eq!(config.hosts, ["db1"]);
+
+    std::env::set_var("POSN_HOSTS", "[]");
+    let err = DeployConfig::from_gonfig_with_args(ConfigBuilder::new(), ["deploy", "staging"])
+        .unwrap_err();
+    std::env::remove_var("POSN_TARGET");
+    std::env::remove_var("POSN_HOSTS");
+    assert!(err.to_string().contains("hosts"), "{}", err);
+}
+
+#[test]
+fn test_from_vec_positionals() {
+    let args = ["app", "in.txt", "--level", "3", "--", "-a.txt", "b.txt"];
+    let cli = Cli::from_vec(args.iter().map(|a| a.to_string()).collect())
+        .with_positional("input", 0)
+        .with_variadic("io.extra", 1);
+
+    let result = cli.collect().unwrap();
+    assert_eq!(result["input"], "in.txt");
+    assert_eq!(
+        result["io"]["extra"],
+        serde_json::json!(["-a.txt", "b.txt"])
+    );
+    assert_eq!(result["level"], 3);
+    assert_eq!(cli.origin("input").as_deref(), Some("CLI argument #1"));
+
+    // Unmapped positional arguments are not settings
+    let result = Cli::from_vec(vec!["app".into(), "in.txt".into()])
+        .collect()
+        .unwrap();
+    assert_eq!(result, serde_json::json!({}));
+}
@@ -0,0 +1,203 @@
+use gonfig::{Cli, ConfigBuilder, ConfigFormat, Error, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use tempfile::{Builder, NamedTempFile};
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "PRINTC", allow_cli, allow_config)]
+struct AppConfig {
+    #[gonfig(default = "\"app\"")]
+    name: String,
+
+    database: Database,
+
+    auth: Auth,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Database {
+    url: String,
+
+    #[gonfig(sensitive)]
+    password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+enum Auth {
+    None,
+    Token {
+        #[gonfig(sensitive)]
+        token: String,
+    },
+}
+
+fn toml_file(content: &str) -> NamedTempFile {
+    let mut file = Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(file, "{}", content).unwrap();
+    file
+}
+
+#[test]
+fn test_config_flag_layers_files_in_order() {
+    let base = toml_file(
+        "[database]\nurl = \"postgres://base\"\npassword = \"hunter2\"\n\n[auth]\nmode = \"none\"",
+    );
+    let local = toml_file("name = \"local\"\n[database]\nurl = \"postgres://local\"");
+
+    let config = AppConfig::from_gonfig_with_args(
+        ConfigBuilder::new(),
+        [
+            "app",
+            "--config",
+            base.path().to_str().unwrap(),
+            "--config",
+            local.path().to_str().unwrap(),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(config.name, "local");
+    assert_eq!(config.database.url, "postgres://local");
+    assert_eq!(config.database.password.as_deref(), Some("hunter2"));
+
+    match AppConfig::from_gonfig_with_args(
+        ConfigBuilder::new(),
+        ["app", "--config", "/nonexistent/app.toml"],
+    ) {
+        Err(Error::Config(msg)) => assert_eq!(msg, "Config file not found: /nonexistent/app.toml"),
+        other => panic!("expected config error, got {:?}", other),
+    }
+
+    let help = AppConfig::gonfig_command().render_help().to_string();
+    assert!(help.contains("--config <PATH>"), "{}", help);
+    assert!(help.contains("--print-config [<FORMAT>]"), "{}", help);
+}
+
+#[test]
+fn test_render_config_redacts_sensitive_fields() {
+    let builder = || {
+        ConfigBuilder::new()
+            .with_defaults(serde_json::json!({
+                "name": "app",
+                "database": { "url": "postgres://db", "password": "hunter2" },
+                "auth": { "mode": "token", "token": "t0ken" }
+            }))
+            .unwrap()
+            .with_schema(AppConfig::gonfig_schema())
+    };
+
+    let yaml = builder().render_config(ConfigFormat::Yaml).unwrap();
+    assert!(yaml.contains("password: <redacted>"), "{}", yaml);
+    assert!(yaml.contains("token: <redacted>"), "{}", yaml);
+    assert!(yaml.contains("url: postgres://db"), "{}", yaml);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&builder().render_config(ConfigFormat::Json).unwrap()).unwrap();
+    assert_eq!(json["database"]["password"], "<redacted>");
+    assert_eq!(json["auth"]["mode"], "token");
+}
+
+#[test]
+fn test_print_config_format() {
+    let format = |args: &[&str]| {
+        let cli = Cli::from_command_args(
+            AppConfig::gonfig_command(),
+            &AppConfig::gonfig_schema(),
+            std::iter::once("app").chain(args.iter().copied()),
+        )?;
+        cli.print_config_format()
+    };
+
+    assert_eq!(format(&[]).unwrap(), None);
+    assert_eq!(
+        format(&["--print-config"]).unwrap(),
+        Some(ConfigFormat::Json)
+    );
+    assert_eq!(
+        format(&["--print-config", "toml"]).unwrap(),
+        Some(ConfigFormat::Toml)
+    );
+    assert!(matches!(
+        format(&["--print-config", "xml"]),
+        Err(Error::Cli(_))
+    ));
+
+    // Control flags are not configuration values
+    let cli = Cli::from_command_args(
+        AppConfig::gonfig_command(),
+        &AppConfig::gonfig_schema(),
+        ["app", "--print-config", "yaml", "--name", "x"],
+    )
+    .unwrap();
+    assert_eq!(
+        gonfig::ConfigSource::collect(&cli).unwrap(),
+        serde_json::json!({ "name": "x" })
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "PRINTK")]
+struct KeyConfig {
+    #[gonfig(alias = "db_url", sensitive)]
+    database_url: String,
+}
+
+#[test]
+fn test_render_config_prints_only_schema_fields() {
+    std::env::set_var("PRINTK_DB_URL", "postgres://user:secret@db");
+    std::env::set_var("PRINTK_API_TOKEN", "t0ken");
+
+    let printed = ConfigBuilder::new()
+        .with_env("PRINTK")
+        .with_schema(KeyConfig::gonfig_schema())
+        .render_config(ConfigFormat::Json);
+
+    std::env::remove_var("PRINTK_DB_URL");
+    std::env::remove_var("PRINTK_API_TOKEN");
+
+    let json: serde_json::Value = serde_json::from_str(&printed.unwrap()).unwrap();
+    assert_eq!(json, serde_json::json!({ "database_url": "<redacted>" }));
+}
+
+#[test]
+fn test_print_config_is_returned_to_the_caller() {
+    std::env::set_var("PRINTC_DATABASE_PASSWORD", "hunter2");
+    let result = AppConfig::from_gonfig_with_args(
+        ConfigBuilder::new(),
+        [
+            "app",
+            "--print-config",
+            "yaml",
+            "--database-url",
+            "postgres://db",
+        ],
+    );
+    std::env::remove_var("PRINTC_DATABASE_PASSWORD");
+
+    match result {
+        Err(Error::PrintConfig(rendered)) => {
+            assert!(rendered.contains("url: postgres://db"), "{}", rendered);
+            assert!(rendered.contains("password: <redacted>"), "{}", rendered);
+        }
+        other => panic!("expected the rendered config, got {:?}", other),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "PRINTN", allow_cli)]
+struct NoFilesConfig {
+    name: Option<String>,
+}
+
+#[test]
+fn test_config_flag_needs_config_files() {
+    let help = NoFilesConfig::gonfig_command().render_help().to_string();
+    assert!(!help.contains("--config"), "{}", help);
+    assert!(help.contains("--print-config"), "{}", help);
+
+    assert!(matches!(
+        NoFilesConfig::from_gonfig_with_args(ConfigBuilder::new(), ["app", "--config", "app.toml"]),
+        Err(Error::Cli(_))
+    ));
+}
@@ -0,0 +1,121 @@
+use gonfig::schema::GonfigSchema;
+use gonfig::{ConfigBuilder, Gonfig};
+use serde::Deserialize;
+use std::io::Write;
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[Gonfig(env_prefix = "RNAM", allow_cli)]
+struct AppConfig {
+    database_url: String,
+
+    #[serde(rename = "maxConn")]
+    #[gonfig(default = "10")]
+    max_connections: u32,
+
+    http_server: HttpServer,
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct HttpServer {
+    listen_port: u16,
+
+    #[serde(default)]
+    tls_enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[Gonfig(
+    env_prefix = "RNAC",
+    allow_cli,
+    rename_all_env = "SCREAMING-KEBAB-CASE",
+    rename_all_cli = "camelCase"
+)]
+struct CustomNames {
+    log_level: String,
+}
+
+fn names<T: GonfigSchema>() -> Vec<(String, String, String)> {
+    T::gonfig_schema()
+        .field_mappings()
+        .into_iter()
+        .map(|m| (m.path, m.env_key, m.cli_key))
+        .collect()
+}
+
+fn row(path: &str, env: &str, cli: &str) -> (String, String, String) {
+    (path.to_string(), env.to_string(), cli.to_string())
+}
+
+#[test]
+fn test_mappings_use_serialized_names() {
+    assert_eq!(
+        names::<AppConfig>(),
+        [
+            row("databaseUrl", "RNAM_DATABASE_URL", "database-url"),
+            row("maxConn", "RNAM_MAX_CONN", "max-conn"),
+            row(
+                "httpServer.listen-port",
+                "RNAM_HTTP_SERVER_LISTEN_PORT",
+                "http-server-listen-port"
+            ),
+            row(
+                "httpServer.tls-enabled",
+                "RNAM_HTTP_SERVER_TLS_ENABLED",
+                "http-server-tls-enabled"
+            ),
+        ]
+    );
+
+    assert_eq!(
+        names::<CustomNames>(),
+        [row("logLevel", "RNAC_LOG-LEVEL", "logLevel")]
+    );
+}
+
+#[test]
+fn test_renamed_fields_read_from_every_source() {
+    let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+    write!(
+        file,
+        r#"{{ "databaseUrl": "postgres://file", "httpServer": {{ "listen-port": 80 }} }}"#
+    )
+    .unwrap();
+    let builder = || ConfigBuilder::new().with_file(file.path()).unwrap();
+
+    let config = AppConfig::from_gonfig_with_args(builder(), ["app"]).unwrap();
+    assert_eq!(
+        config,
+        AppConfig {
+            database_url: "postgres://file".to_string(),
+            max_connections: 10,
+            http_server: HttpServer {
+                listen_port: 80,
+                tls_enabled: false,
+            },
+        }
+    );
+
+    std::env::set_var("RNAM_MAX_CONN", "25");
+    std::env::set_var("RNAM_HTTP_SERVER_TLS_ENABLED", "true");
+    let config = AppConfig::from_gonfig_with_args(
+        builder(),
+        [
+            "app",
+            "--database-url",
+            "postgres://cli",
+            "--http-server-listen-port",
+            "8443",
+        ],
+    );
+    std::env::remove_var("RNAM_MAX_CONN");
+    std::env::remove_var("RNAM_HTTP_SERVER_TLS_ENABLED");
+
+    let config = config.unwrap();
+    assert_eq!(config.database_url, "postgres://cli");
+    assert_eq!(config.max_connections, 25);
+    assert_eq!(config.http_server.listen_port, 8443);
+    assert!(config.http_server.tls_enabled);
+}
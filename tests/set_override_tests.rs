@@ -0,0 +1,123 @@
+use gonfig::{Cli, ConfigBuilder, ConfigFormat, ConfigSource, Error, Gonfig};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "SETO", allow_cli, allow_set)]
+struct AppConfig {
+    name: String,
+
+    servers: Vec<Server>,
+
+    #[gonfig(sources = "file")]
+    secret: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+fn config_file() -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "name = \"app\"\nsecret = \"s3cret\"\n\n[[servers]]\nhost = \"a\"\nport = 80\n\n[[servers]]\nhost = \"b\"\nport = 80"
+    )
+    .unwrap();
+    file
+}
+
+fn load(file: &NamedTempFile, args: &[&str]) -> gonfig::Result<AppConfig> {
+    let builder = ConfigBuilder::new().with_file_format(file.path(), ConfigFormat::Toml)?;
+    AppConfig::from_gonfig_with_args(builder, std::iter::once("app").chain(args.iter().copied()))
+}
+
+#[test]
+fn test_set_overrides_nested_and_array_values() {
+    let file = config_file();
+    let config = load(
+        &file,
+        &[
+            "--set",
+            "servers[1].port=81",
+            "--name",
+            "flag",
+            "--set=name=set",
+            "--set-json",
+            "servers[2]={\"host\": \"c\", \"port\": 82}",
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(config.name, "set");
+    assert_eq!(
+        config.servers,
+        vec![
+            Server {
+                host: "a".into(),
+                port: 80
+            },
+            Server {
+                host: "b".into(),
+                port: 81
+            },
+            Server {
+                host: "c".into(),
+                port: 82
+            },
+        ]
+    );
+    assert!(AppConfig::gonfig_command()
+        .render_help()
+        .to_string()
+        .contains("--set <PATH=VALUE>"));
+}
+
+#[test]
+fn test_set_respects_source_restrictions() {
+    let file = config_file();
+    let builder = ConfigBuilder::new()
+        .with_file_format(file.path(), ConfigFormat::Toml)
+        .unwrap()
+        .with_cli_custom(
+            Cli::from_vec(vec!["app".into(), "--set".into(), "secret=leaked".into()])
+                .allow_set(true),
+        );
+
+    let (value, warnings) = builder
+        .with_schema(<AppConfig as gonfig::GonfigSchema>::gonfig_schema())
+        .build_with_warnings::<AppConfig>()
+        .unwrap();
+    assert_eq!(value.secret, "s3cret");
+    assert_eq!(
+        warnings,
+        vec![
+            "Ignoring secret from CLI flag --set secret: it can only be set from config file"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_set_errors_and_opt_in() {
+    let file = config_file();
+    match load(&file, &["--set-json", "name={"]) {
+        Err(Error::Cli(msg)) => assert!(
+            msg.starts_with("Invalid JSON for --set-json name"),
+            "{}",
+            msg
+        ),
+        other => panic!("expected CLI error, got {:?}", other),
+    }
+    match load(&file, &["--set", "name"]) {
+        Err(Error::Cli(msg)) => assert_eq!(msg, "--set expects PATH=VALUE, got \"name\""),
+        other => panic!("expected CLI error, got {:?}", other),
+    }
+
+    // Without `allow_set`, `--set` is an ordinary key
+    let cli = Cli::from_vec(vec!["app".into(), "--set".into(), "a=1".into()]);
+    assert_eq!(cli.collect().unwrap()["set"], "a=1");
+}
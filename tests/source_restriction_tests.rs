@@ -0,0 +1,121 @@
+use gonfig::{Cli, ConfigBuilder, Environment, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "SRCR")]
+struct DbConfig {
+    host: String,
+
+    #[gonfig(sources = "file,env")]
+    password: String,
+
+    #[gonfig(no_env)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "SRCR_NOENV", allow_env = false)]
+struct NoEnvConfig {
+    #[gonfig(default = "\"from-default\"")]
+    name: String,
+}
+
+fn args(args: &[&str]) -> Cli {
+    let schema = DbConfig::gonfig_schema();
+    let mut cli = Cli::from_vec(
+        std::iter::once("program")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect(),
+    );
+    for mapping in schema.field_mappings() {
+        cli = cli.with_field_mapping(&mapping.path, &mapping.cli_key);
+    }
+    cli
+}
+
+#[test]
+fn test_field_sources_restrict_cli() {
+    let (config, warnings) = ConfigBuilder::new()
+        .with_schema(DbConfig::gonfig_schema())
+        .with_defaults(json!({ "host": "localhost", "password": "from-file" }))
+        .unwrap()
+        .with_cli_custom(args(&["--password", "from-argv", "--host", "db"]))
+        .build_with_warnings::<DbConfig>()
+        .unwrap();
+
+    assert_eq!(config.host, "db");
+    assert_eq!(config.password, "from-file");
+    assert_eq!(
+        warnings,
+        vec![
+            "Ignoring password from CLI flag --password: it can only be set from config file, environment"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_no_env_field() {
+    env::set_var("SRCR_TOKEN", "from-env");
+    env::set_var("SRCR_PASSWORD", "secret");
+
+    let (config, warnings) = ConfigBuilder::new()
+        .with_schema(DbConfig::gonfig_schema())
+        .with_defaults(json!({ "host": "localhost" }))
+        .unwrap()
+        .with_env_custom(
+            Environment::new()
+                .with_prefix("SRCR")
+                .with_field_mapping("token", "SRCR_TOKEN")
+                .with_field_mapping("password", "SRCR_PASSWORD"),
+        )
+        .with_cli_custom(args(&["--token", "from-argv"]))
+        .build_with_warnings::<DbConfig>()
+        .unwrap();
+
+    env::remove_var("SRCR_TOKEN");
+    env::remove_var("SRCR_PASSWORD");
+
+    assert_eq!(config.password, "secret");
+    assert_eq!(config.token.as_deref(), Some("from-argv"));
+    assert!(
+        warnings[0].starts_with("Ignoring token from env var SRCR_TOKEN"),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_struct_allow_env_false() {
+    env::set_var("SRCR_NOENV_NAME", "from-env");
+    let config = NoEnvConfig::from_gonfig().unwrap();
+    env::remove_var("SRCR_NOENV_NAME");
+
+    assert_eq!(config.name, "from-default");
+}
+
+#[test]
+fn test_clap_command_follows_field_sources() {
+    let schema = DbConfig::gonfig_schema();
+    let help = Cli::command(&schema).render_help().to_string();
+
+    assert!(!help.contains("--password"), "{}", help);
+    assert!(help.contains("--token"), "{}", help);
+    assert!(!help.contains("SRCR_TOKEN"), "{}", help);
+    assert!(help.contains("[env: SRCR_HOST]"), "{}", help);
+
+    let err = Cli::from_command_args(
+        Cli::command(&schema),
+        &schema,
+        ["program", "--password", "from-argv"],
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("unexpected argument '--password'"),
+        "{}",
+        err
+    );
+}
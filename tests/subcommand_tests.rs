@@ -0,0 +1,182 @@
+use gonfig::{Cli, ConfigBuilder, ConfigSource, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use tempfile::{Builder, NamedTempFile};
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "SUBC", allow_cli)]
+struct AppConfig {
+    #[gonfig(default = "\"info\"")]
+    log_level: String,
+
+    #[gonfig(default = "\"/var/lib/app\"")]
+    data_dir: String,
+
+    #[gonfig(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Command {
+    /// Serve HTTP requests.
+    Serve {
+        #[gonfig(default = "8080")]
+        port: u16,
+        log_level: String,
+    },
+    /// Apply pending database migrations.
+    Migrate(MigrateConfig),
+    /// Run background jobs.
+    Worker,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct MigrateConfig {
+    #[gonfig(default = "false")]
+    dry_run: bool,
+}
+
+fn toml_file(content: &str) -> NamedTempFile {
+    let mut file = Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(file, "{}", content).unwrap();
+    file
+}
+
+#[test]
+fn test_subcommand_section_from_file_env_and_flags() {
+    let file =
+        toml_file("log_level = \"warn\"\n\n[serve]\nport = 3000\n\n[migrate]\ndry_run = true");
+    let builder = || ConfigBuilder::new().with_file(file.path()).unwrap();
+
+    let config = AppConfig::from_gonfig_with_args(builder(), ["app", "serve"]).unwrap();
+    assert_eq!(
+        config.command,
+        Command::Serve {
+            port: 3000,
+            log_level: "warn".to_string(),
+        }
+    );
+
+    std::env::set_var("SUBC_SERVE__PORT", "4000");
+    let config = AppConfig::from_gonfig_with_args(builder(), ["app", "serve"]).unwrap();
+    assert_eq!(
+        config.command,
+        Command::Serve {
+            port: 4000,
+            log_level: "warn".to_string(),
+        }
+    );
+
+    // Flags of the subcommand set its section, shared flags are accepted
+    // on either side of it
+    let config = AppConfig::from_gonfig_with_args(
+        builder(),
+        [
+            "app",
+            "serve",
+            "--port",
+            "5000",
+            "--log-level",
+            "debug",
+            "--data-dir",
+            "/tmp",
+        ],
+    )
+    .unwrap();
+    std::env::remove_var("SUBC_SERVE__PORT");
+    assert_eq!(config.log_level, "warn");
+    assert_eq!(config.data_dir, "/tmp");
+    assert_eq!(
+        config.command,
+        Command::Serve {
+            port: 5000,
+            log_level: "debug".to_string(),
+        }
+    );
+
+    let config = AppConfig::from_gonfig_with_args(builder(), ["app", "migrate"]).unwrap();
+    assert_eq!(
+        config.command,
+        Command::Migrate(MigrateConfig { dry_run: true })
+    );
+
+    let config = AppConfig::from_gonfig_with_args(builder(), ["app", "worker"]).unwrap();
+    assert_eq!(config.command, Command::Worker);
+}
+
+#[test]
+fn test_subcommand_defaults_and_selector() {
+    let schema = AppConfig::gonfig_schema();
+    let matches = Cli::command(&schema)
+        .try_get_matches_from(["app", "--log-level", "error", "serve"])
+        .unwrap();
+    let cli = Cli::from_matches(&matches, &schema);
+    assert_eq!(cli.subcommand(), Some("serve"));
+
+    let value = ConfigBuilder::new()
+        .with_defaults(schema.defaults())
+        .unwrap()
+        .with_schema(schema.clone())
+        .with_cli_custom(cli)
+        .build_value()
+        .unwrap();
+    assert_eq!(value["log_level"], "error");
+    assert_eq!(
+        value["command"],
+        serde_json::json!({ "serve": { "port": 8080, "log_level": "error" } })
+    );
+    assert!(value.get("serve").is_none());
+    assert!(value.get("migrate").is_none());
+
+    // Without a subcommand, a file can name one
+    let file = toml_file("command = \"migrate\"");
+    let config = AppConfig::from_gonfig_with_args(
+        ConfigBuilder::new().with_file(file.path()).unwrap(),
+        ["app"],
+    )
+    .unwrap();
+    assert_eq!(
+        config.command,
+        Command::Migrate(MigrateConfig { dry_run: false })
+    );
+
+    let file = toml_file("command = \"deploy\"");
+    let err = AppConfig::from_gonfig_with_args(
+        ConfigBuilder::new().with_file(file.path()).unwrap(),
+        ["app"],
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("serve"), "{}", err);
+}
+
+#[test]
+fn test_subcommand_help_and_origin() {
+    let mut command = AppConfig::gonfig_command();
+    let help = command.render_help().to_string();
+    assert!(help.contains("serve"), "{}", help);
+    assert!(
+        help.contains("Apply pending database migrations."),
+        "{}",
+        help
+    );
+
+    let serve_help = command
+        .find_subcommand_mut("serve")
+        .unwrap()
+        .render_help()
+        .to_string();
+    assert!(serve_help.contains("--port"), "{}", serve_help);
+
+    let schema = AppConfig::gonfig_schema();
+    let cli = Cli::from_command_args(
+        Cli::command(&schema),
+        &schema,
+        ["app", "serve", "--port", "81"],
+    )
+    .unwrap();
+    assert_eq!(
+        cli.origin("serve.port").as_deref(),
+        Some("CLI flag serve --port")
+    );
+}
@@ -0,0 +1,115 @@
+use gonfig::{ConfigBuilder, ConfigFormat, Gonfig};
+use serde::Deserialize;
+use std::io::Write;
+
+/// Example service configuration
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "TMPL")]
+struct AppConfig {
+    /// Name shown in logs
+    #[gonfig(default = "\"app\"")]
+    name: String,
+
+    /// Port to listen on
+    #[gonfig(default = "8080")]
+    port: u16,
+
+    tags: Vec<String>,
+
+    database: Database,
+
+    /// How clients authenticate
+    auth: Auth,
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+struct Database {
+    /// Connection URL
+    url: String,
+
+    #[gonfig(default = "5")]
+    pool_size: u32,
+}
+
+#[derive(Debug, Deserialize, Gonfig, PartialEq)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+enum Auth {
+    Token { token: String },
+    None,
+}
+
+const TOML_TEMPLATE: &str = r#"# Example service configuration
+
+# Name shown in logs
+name = "app"
+
+# Port to listen on
+port = 8080
+tags = []
+
+[database]
+# Connection URL
+url = ""
+pool_size = 5
+
+# How clients authenticate
+#
+# One of: token, none
+[auth]
+mode = "token"
+token = ""
+"#;
+
+#[test]
+fn test_toml_template_matches_checked_in_example() {
+    assert_eq!(AppConfig::template(ConfigFormat::Toml), TOML_TEMPLATE);
+}
+
+#[test]
+fn test_yaml_and_json_templates() {
+    let yaml = AppConfig::template(ConfigFormat::Yaml);
+    assert!(
+        yaml.starts_with(
+            "# Example service configuration\n\n# Name shown in logs\nname: \"app\"\n"
+        ),
+        "{}",
+        yaml
+    );
+    assert!(
+        yaml.contains("database:\n  # Connection URL\n  url: \"\"\n  pool_size: 5\n"),
+        "{}",
+        yaml
+    );
+
+    let json = AppConfig::template(ConfigFormat::Json);
+    assert!(json.starts_with("{\n  \"name\": \"app\",\n"), "{}", json);
+    assert!(json.ends_with("    \"token\": \"\"\n  }\n}\n"), "{}", json);
+    assert!(!json.contains("//"), "{}", json);
+    serde_json::from_str::<serde_json::Value>(&json).unwrap();
+}
+
+#[test]
+fn test_templates_load_back() {
+    for (format, suffix) in [
+        (ConfigFormat::Toml, ".toml"),
+        (ConfigFormat::Yaml, ".yaml"),
+        (ConfigFormat::Json, ".json"),
+    ] {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        write!(file, "{}", AppConfig::template(format.clone())).unwrap();
+
+        let config: AppConfig = ConfigBuilder::new()
+            .with_file(file.path())
+            .unwrap()
+            .build()
+            .unwrap_or_else(|e| panic!("{:?}: {}", format, e));
+        assert_eq!(config.port, 8080, "{:?}", format);
+        assert_eq!(config.database.pool_size, 5, "{:?}", format);
+        assert_eq!(
+            config.auth,
+            Auth::Token {
+                token: String::new()
+            }
+        );
+    }
+}
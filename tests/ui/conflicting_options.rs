@@ -0,0 +1,26 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+fn default_port() -> u16 {
+    8080
+}
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    #[gonfig(default = "80", default_fn = "default_port")]
+    port: u16,
+
+    #[gonfig(no_env, env_name = "TOKEN")]
+    token: String,
+
+    #[gonfig(positional, short = 'i')]
+    input: String,
+
+    #[gonfig(positional(index = 0))]
+    output: String,
+
+    #[gonfig(range(min = 10, max = 1))]
+    workers: u32,
+}
+
+fn main() {}
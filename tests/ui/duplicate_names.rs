@@ -0,0 +1,19 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    #[gonfig(env_name = "DATABASE_URL", short = 'd')]
+    primary_url: String,
+
+    #[gonfig(env_name = "DATABASE_URL")]
+    replica_url: String,
+
+    #[gonfig(short = 'd', cli_name = "debug")]
+    debug: bool,
+
+    #[gonfig(cli_name = "debug")]
+    verbose: bool,
+}
+
+fn main() {}
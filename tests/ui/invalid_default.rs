@@ -0,0 +1,16 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    #[gonfig(default = "80x")]
+    port: u16,
+
+    #[gonfig(default = "true")]
+    name: String,
+
+    #[gonfig(default = "18446744073709551616")]
+    total: u128,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Debug,
+    Info,
+}
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    #[gonfig(default = "INFO")]
+    level: Level,
+
+    #[gonfig(default = "bogus")]
+    fallback: Option<Level>,
+}
+
+fn main() {}
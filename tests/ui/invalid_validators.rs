@@ -0,0 +1,16 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    #[gonfig(regex = "^[a-z+$")]
+    name: String,
+
+    #[gonfig(one_of = [1, 300])]
+    level: u8,
+
+    #[gonfig(one_of = [18446744073709551616])]
+    total: Option<u128>,
+}
+
+fn main() {}
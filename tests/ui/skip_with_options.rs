@@ -0,0 +1,18 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    port: u16,
+
+    #[skip]
+    #[gonfig(env_name = "CACHE_DIR")]
+    #[serde(skip)]
+    cache_dir: Option<String>,
+
+    #[gonfig(skip, default = "1")]
+    #[serde(skip)]
+    retries: u32,
+}
+
+fn main() {}
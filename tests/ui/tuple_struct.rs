@@ -0,0 +1,7 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+struct Config(String, u16);
+
+fn main() {}
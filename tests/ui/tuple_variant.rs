@@ -0,0 +1,10 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+enum Backend {
+    Memory,
+    Remote(String, u16),
+}
+
+fn main() {}
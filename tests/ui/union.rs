@@ -0,0 +1,9 @@
+use gonfig::Gonfig;
+
+#[derive(Gonfig)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use gonfig::Gonfig;
+use serde::Deserialize;
+
+#[derive(Deserialize, Gonfig)]
+struct Config {
+    #[gonfig(env = "PORT")]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,200 @@
+use gonfig::validation::{FieldValidator, Regex};
+use gonfig::{ConfigBuilder, Error, Gonfig, GonfigSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+
+fn even(value: &u32) -> Result<(), String> {
+    if value % 2 == 1 {
+        return Err(format!("{} is not even", value));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+#[Gonfig(env_prefix = "VALID")]
+struct ServerConfig {
+    #[gonfig(range(min = 1, max = 65535))]
+    port: u32,
+
+    #[gonfig(length(min = 3, max = 16), regex = "^[a-z-]+$")]
+    name: String,
+
+    #[gonfig(one_of = ["debug", "info", "warn"])]
+    log_level: String,
+
+    #[gonfig(url)]
+    endpoint: String,
+
+    #[gonfig(non_empty)]
+    hosts: Vec<String>,
+
+    #[gonfig(custom = "even")]
+    workers: u32,
+
+    limits: Limits,
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Limits {
+    #[gonfig(range(min = -1.5, max = 10))]
+    burst: f64,
+}
+
+fn valid() -> serde_json::Value {
+    json!({
+        "port": 8080,
+        "name": "api-server",
+        "log_level": "info",
+        "endpoint": "https://example.com/api",
+        "hosts": ["a"],
+        "workers": 4,
+        "limits": { "burst": 2.5 }
+    })
+}
+
+fn build(value: serde_json::Value) -> gonfig::Result<ServerConfig> {
+    ConfigBuilder::new()
+        .with_schema(ServerConfig::gonfig_schema())
+        .with_defaults(value)?
+        .build()
+}
+
+#[test]
+fn test_valid_config_passes() {
+    let config = build(valid()).unwrap();
+    assert_eq!(config.port, 8080);
+}
+
+#[test]
+fn test_all_failures_reported() {
+    let result = build(json!({
+        "port": 0,
+        "name": "X",
+        "log_level": "trace",
+        "endpoint": "not a url",
+        "hosts": [],
+        "workers": 3,
+        "limits": { "burst": -2 }
+    }));
+
+    let msg = match result {
+        Err(Error::Validation(msg)) => msg,
+        other => panic!("expected validation error, got {:?}", other),
+    };
+
+    assert!(msg.contains("port: must be between 1 and 65535"), "{}", msg);
+    assert!(
+        msg.contains("name: length must be between 3 and 16"),
+        "{}",
+        msg
+    );
+    assert!(msg.contains("name: must match pattern"), "{}", msg);
+    assert!(msg.contains("log_level: must be one of"), "{}", msg);
+    assert!(msg.contains("endpoint: must be an absolute URL"), "{}", msg);
+    assert!(msg.contains("hosts: must not be empty"), "{}", msg);
+    assert!(msg.contains("workers: 3 is not even"), "{}", msg);
+    assert!(
+        msg.contains("limits.burst: must be between -1.5 and 10"),
+        "{}",
+        msg
+    );
+    assert!(msg.contains("(set by default value)"), "{}", msg);
+}
+
+#[test]
+fn test_failure_names_env_var() {
+    env::set_var("VALID_PORT", "70000");
+    env::set_var("VALID_LIMITS_BURST", "11");
+
+    let result = ConfigBuilder::new()
+        .with_schema(ServerConfig::gonfig_schema())
+        .with_defaults(valid())
+        .unwrap()
+        .with_env_custom(
+            gonfig::Environment::new()
+                .with_prefix("VALID")
+                .with_field_mapping("port", "VALID_PORT")
+                .with_field_mapping("limits.burst", "VALID_LIMITS_BURST"),
+        )
+        .build::<ServerConfig>();
+
+    env::remove_var("VALID_PORT");
+    env::remove_var("VALID_LIMITS_BURST");
+
+    let msg = match result {
+        Err(Error::Validation(msg)) => msg,
+        other => panic!("expected validation error, got {:?}", other),
+    };
+    assert!(
+        msg.contains("port: must be between 1 and 65535 (got 70000) (set by env var VALID_PORT)"),
+        "{}",
+        msg
+    );
+    assert!(
+        msg.contains("(set by env var VALID_LIMITS_BURST)"),
+        "{}",
+        msg
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, Gonfig, PartialEq)]
+struct Quota {
+    #[gonfig(one_of = [0, 18446744073709551615])]
+    bytes: u64,
+
+    limit: Option<u64>,
+
+    #[gonfig(required_if = "limit=9007199254740993")]
+    reason: Option<String>,
+}
+
+#[test]
+fn test_one_of_integers_beyond_i64() {
+    let build = |bytes: u64| {
+        ConfigBuilder::new()
+            .with_schema(Quota::gonfig_schema())
+            .with_defaults(json!({ "bytes": bytes, "reason": "quota" }))
+            .unwrap()
+            .build::<Quota>()
+    };
+
+    assert_eq!(build(u64::MAX).unwrap().bytes, u64::MAX);
+    let msg = build(1).unwrap_err().to_string();
+    assert!(
+        msg.contains("bytes: must be one of 0, 18446744073709551615 (got 1)"),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_large_integers_compare_exactly() {
+    // 2^53 + 1 and 2^53 are the same `f64`
+    let allowed = FieldValidator::OneOf(vec![json!(9007199254740993u64)]);
+    assert!(allowed.check(&json!(9007199254740993u64)).is_ok());
+    assert!(allowed.check(&json!(9007199254740992u64)).is_err());
+    assert!(FieldValidator::OneOf(vec![json!(5)])
+        .check(&json!(5.0))
+        .is_ok());
+
+    let build = |limit: u64| {
+        ConfigBuilder::new()
+            .with_defaults(json!({ "bytes": 0, "limit": limit }))
+            .unwrap()
+            .with_schema(Quota::gonfig_schema())
+            .build_value()
+    };
+    assert!(build(9007199254740992).is_ok());
+    assert!(build(9007199254740993).is_err());
+}
+
+#[test]
+fn test_regex_validator_holds_the_compiled_pattern() {
+    let name = FieldValidator::Regex(Regex::new("^[a-z-]+$").unwrap());
+    assert!(name.check(&json!("api-server")).is_ok());
+    assert_eq!(
+        name.check(&json!("API")).unwrap_err(),
+        "must match pattern '^[a-z-]+$' (got 'API')"
+    );
+}